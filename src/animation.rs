@@ -0,0 +1,152 @@
+// What happens to the pieces on the board during a move, so renderers can animate castling, en passant, promotions,
+// drops and Atomic explosions without knowing the rules.
+//
+// The steps are listed in the order they should be shown: the pieces that are captured, the pieces that move, the
+// promotion, and last the pieces that explode in Atomic.
+
+use crate::{Game, Move, PieceColor, PieceType, Square};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Animation {
+    // the piece on "from" slides to "to"
+    Movement { from: Square, to: Square },
+    // the piece on the square is taken off the board
+    Removal(Square),
+    // the piece on the square turns into another type, for promotions
+    Transformation { square: Square, piece_type: PieceType },
+    // a piece is put on the board, for Crazyhouse drops
+    Placement { square: Square, piece_type: PieceType, color: PieceColor },
+}
+
+impl Game {
+    // returns the steps to animate a legal move, None if the move is not legal
+    // a promotion without a promotion set is animated without the Transformation
+    pub fn move_animation(&self, m: &Move) -> Option<Vec<Animation>> {
+        let mut after = self.clone();
+        let legal = match (m.drop, m.promotion) {
+            (None, None) => after.do_move(&m.from, &m.to),
+            _ => after.make_move(m),
+        };
+        if !legal {
+            return None;
+        }
+
+        if let Some(piece_type) = m.drop {
+            return Some(vec![Animation::Placement { square: m.to, piece_type, color: self.turn }]);
+        }
+
+        let piece = &self.live_pieces[&m.from];
+        let mut movements = vec![];
+        // the squares whose pieces are accounted for by a movement
+        let mut moved_from = vec![m.from];
+
+        // the square of the piece that is captured, it is behind the pawn in en passant
+        let mut captured = match self.live_pieces.get(&m.to) {
+            Some(other) if other.color != piece.color => Some(m.to),
+            None if piece.piece_type == PieceType::Pawn && m.from.x != m.to.x => Some(m.to.moved(0, -piece.get_direction())),
+            _ => None,
+        };
+
+        let mut to = m.to;
+        if piece.piece_type == PieceType::King {
+            if let Some(rook) = self.castling_rook_for_move(piece, m.to) {
+                let short = rook.x > piece.pos.x;
+                to = Square::at(if short { 6 } else { 2 }, piece.pos.y);
+                let rook_to = Square::at(if short { 5 } else { 3 }, piece.pos.y);
+                movements.push(Animation::Movement { from: m.from, to });
+                movements.push(Animation::Movement { from: rook, to: rook_to });
+                moved_from.push(rook);
+                captured = None;
+            }
+        }
+        if movements.is_empty() {
+            movements.push(Animation::Movement { from: m.from, to });
+        }
+
+        let mut steps = Vec::new();
+        let mut explosions = Vec::new();
+
+        // the captured piece comes off first, the other pieces that are gone were blown up in Atomic
+        let mut squares: Vec<&Square> = self.live_pieces.keys().filter(|square| !moved_from.contains(square)).collect();
+        squares.sort_by_key(|square| (square.y, square.x));
+        for square in squares {
+            let before = &self.live_pieces[square];
+            let same = after.live_pieces.get(square).is_some_and(|p| p.color == before.color && p.piece_type == before.piece_type);
+            if !same {
+                if Some(*square) == captured {
+                    steps.push(Animation::Removal(*square));
+                } else {
+                    explosions.push(Animation::Removal(*square));
+                }
+            }
+        }
+
+        steps.extend(movements);
+
+        match after.live_pieces.get(&to) {
+            Some(landed) if landed.piece_type != piece.piece_type => {
+                steps.push(Animation::Transformation { square: to, piece_type: landed.piece_type });
+            },
+            // the piece that captured explodes as well
+            None => steps.push(Animation::Removal(to)),
+            _ => (),
+        }
+
+        steps.extend(explosions);
+        Some(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_animation() {
+        let game = Game::new();
+        let animation = game.move_animation(&Move::from_uci("e2e4").unwrap()).unwrap();
+        assert_eq!(animation, vec![Animation::Movement { from: Square::E2, to: Square::E4 }]);
+        assert!(game.move_animation(&Move::from_uci("e2e5").unwrap()).is_none());
+
+        // castling moves the rook as well
+        let game = Game::from_fen("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+        let animation = game.move_animation(&Move::from_uci("e1g1").unwrap()).unwrap();
+        assert!(animation == vec![
+            Animation::Movement { from: Square::E1, to: Square::G1 },
+            Animation::Movement { from: Square::H1, to: Square::F1 },
+        ]);
+
+        // the pawn taken en passant is not on the square moved to
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        let animation = game.move_animation(&Move::from_uci("e5d6").unwrap()).unwrap();
+        assert!(animation == vec![
+            Animation::Removal(Square::D5),
+            Animation::Movement { from: Square::E5, to: Square::D6 },
+        ]);
+
+        // a capture that promotes
+        let game = Game::from_fen("2r1k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        let animation = game.move_animation(&Move::from_uci("b7c8n").unwrap()).unwrap();
+        assert!(animation == vec![
+            Animation::Removal(Square::C8),
+            Animation::Movement { from: Square::B7, to: Square::C8 },
+            Animation::Transformation { square: Square::C8, piece_type: PieceType::Knight },
+        ]);
+
+        // in Atomic the capturing piece and the pieces around explode after the move
+        let game = Game::from_fen_variant("4k3/8/8/2nbr3/8/8/3Q4/4K3 w - - 0 1", Variant::Atomic);
+        let animation = game.move_animation(&Move::from_uci("d2d5").unwrap()).unwrap();
+        assert!(animation == vec![
+            Animation::Removal(Square::D5),
+            Animation::Movement { from: Square::D2, to: Square::D5 },
+            Animation::Removal(Square::D5),
+            Animation::Removal(Square::C5),
+            Animation::Removal(Square::E5),
+        ]);
+
+        let game = Game::from_fen_variant("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1", Variant::Crazyhouse);
+        let animation = game.move_animation(&Move::new_drop(PieceType::Knight, Square::F3)).unwrap();
+        assert_eq!(animation, vec![Animation::Placement { square: Square::F3, piece_type: PieceType::Knight, color: PieceColor::White }]);
+    }
+}
@@ -0,0 +1,361 @@
+// Plays chess in the terminal, against another person at the same keyboard or against the engine.
+//
+// usage: chess [--engine white|black|both] [--movetime <ms>] [--depth <n>] [--fen <fen>] [--variant <name>] [--ascii]
+//
+// Moves are typed in SAN (Nf3, exd5, O-O, e8=Q) or UCI (g1f3, e7e8q). The other commands are listed by help.
+// The last move is shown with brackets around its squares. --ascii writes the pieces as letters instead of Unicode.
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::Duration;
+
+use oscae_chess::search::{self, Engine, SearchLimits};
+use oscae_chess::variant::Variant;
+use oscae_chess::*;
+
+const HELP: &str = "commands:
+  <move>   a move in SAN (Nf3, O-O, e8=Q) or UCI (g1f3, e7e8q)
+  go       the engine plays a move for the side to move
+  undo     takes back the last move, and the engine's reply before it
+  fen      prints the position as FEN
+  pgn      prints the game as PGN
+  flip     turns the board around
+  draw     ends the game in a draw
+  resign   the side to move resigns
+  new      starts over
+  help     prints this
+  quit     exits";
+
+struct Options {
+    engine_color: Option<PieceColor>,
+    engine_both: bool,
+    limits: SearchLimits,
+    fen: Option<String>,
+    variant: Variant,
+    ascii: bool,
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            eprintln!("usage: chess [--engine white|black|both] [--movetime <ms>] [--depth <n>] [--fen <fen>] [--variant <name>] [--ascii]");
+            process::exit(1);
+        },
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut options = Options {
+        engine_color: None,
+        engine_both: false,
+        limits: SearchLimits::default(),
+        fen: None,
+        variant: Variant::Standard,
+        ascii: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--ascii" {
+            options.ascii = true;
+            i += 1;
+            continue;
+        }
+
+        let value = args.get(i + 1).ok_or(format!("{} needs a value", args[i]))?;
+        match args[i].as_str() {
+            "--engine" => match value.as_str() {
+                "white" => options.engine_color = Some(PieceColor::White),
+                "black" => options.engine_color = Some(PieceColor::Black),
+                "both" => options.engine_both = true,
+                _ => return Err(format!("--engine takes white, black or both, not {}", value)),
+            },
+            "--movetime" => options.limits.movetime = Some(Duration::from_millis(parse(value)?)),
+            "--depth" => options.limits.depth = Some(parse(value)?),
+            "--fen" => options.fen = Some(value.clone()),
+            "--variant" => options.variant = Variant::from_name(value).ok_or(format!("unknown variant {}", value))?,
+            arg => return Err(format!("unknown option {}", arg)),
+        }
+        i += 2;
+    }
+
+    if options.limits.depth.is_none() && options.limits.movetime.is_none() {
+        options.limits.movetime = Some(Duration::from_secs(1));
+    }
+
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let new_game = || match &options.fen {
+        Some(fen) => Game::from_fen_variant(fen, options.variant),
+        None => Game::new_variant(options.variant),
+    };
+
+    let engine = Engine::new();
+    let mut game = new_game();
+    // the board is shown from black's side when the engine plays white
+    let mut flipped = options.engine_color == Some(PieceColor::White);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut show_board = true;
+
+    loop {
+        if show_board {
+            println!();
+            print!("{}", board(&game, flipped, options.ascii));
+            if game.result != ChessResult::Ongoing {
+                println!("{}", result_text(&game));
+            }
+        }
+        show_board = true;
+
+        let engine_to_move = options.engine_both || options.engine_color == Some(game.turn);
+        if engine_to_move && game.result == ChessResult::Ongoing {
+            engine_move(&engine, &mut game, &options.limits);
+            continue;
+        }
+
+        print!("{}> ", color_name(game.turn));
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(|e| e.to_string())?,
+            None => return Ok(()),
+        };
+
+        match line.trim() {
+            "" => show_board = false,
+            "quit" | "exit" => return Ok(()),
+            "help" => {
+                println!("{}", HELP);
+                show_board = false;
+            },
+            "fen" => {
+                println!("{}", game.to_fen());
+                show_board = false;
+            },
+            "pgn" => {
+                println!("{}", pgn(&game));
+                show_board = false;
+            },
+            "flip" => flipped = !flipped,
+            "new" => game = new_game(),
+            "go" => {
+                if game.result == ChessResult::Ongoing {
+                    engine_move(&engine, &mut game, &options.limits);
+                }
+            },
+            "undo" => {
+                // against the engine its reply is taken back too so it is the player's turn again,
+                // unless the player's move ended the game and the engine never replied
+                let players_move_ended_game = game.result != ChessResult::Ongoing && options.engine_color == Some(game.turn);
+                let plies = if options.engine_color.is_some() && !players_move_ended_game && game.history().len() >= 2 { 2 } else { 1 };
+                match undo(&game, plies) {
+                    Some(undone) => game = undone,
+                    None => {
+                        println!("there is nothing to undo");
+                        show_board = false;
+                    },
+                }
+            },
+            "draw" => game.declare_draw(),
+            "resign" => game.declare_win(!game.turn),
+            input => {
+                if game.result != ChessResult::Ongoing {
+                    println!("the game is over, type new to start again");
+                    show_board = false;
+                    continue;
+                }
+                match parse_move(&game, input) {
+                    Some(m) => _ = game.make_move(&m),
+                    None => {
+                        println!("{} is not a legal move or command, type help for the commands", input);
+                        show_board = false;
+                    },
+                }
+            },
+        }
+    }
+}
+
+// reads a move in UCI or SAN
+fn parse_move(game: &Game, input: &str) -> Option<Move> {
+    match Move::from_uci(input) {
+        Some(m) if game.get_all_moves().contains(&m) => Some(m),
+        _ => game.move_from_san(input),
+    }
+}
+
+fn engine_move(engine: &Engine, game: &mut Game, limits: &SearchLimits) {
+    let result = engine.search(game, limits);
+    if let Some(m) = result.best_move {
+        let san = game.move_to_san(&m).unwrap_or(m.to_uci());
+        let score = match search::mate_in(result.score) {
+            Some(moves) => format!("mate in {}", moves),
+            None => format!("{:+.2}", result.score as f64 / 100.0),
+        };
+        println!("engine plays {} ({}, depth {})", san, score, result.depth);
+        game.make_move(&m);
+    }
+}
+
+// returns the game with the last plies taken back, None if there are not that many moves
+fn undo(game: &Game, plies: usize) -> Option<Game> {
+    let history = game.history();
+    if history.len() < plies {
+        return None;
+    }
+
+    let mut undone = Game::from_fen_variant(game.start_fen(), game.variant());
+    undone.chess960 = game.chess960;
+    for m in &history[..history.len() - plies] {
+        undone.make_move(m);
+    }
+    Some(undone)
+}
+
+// draws the board with coordinates, the squares of the last move are put in brackets
+fn board(game: &Game, flipped: bool, ascii: bool) -> String {
+    let mut text = String::new();
+    let mut files = File::ALL.to_vec();
+    let mut ranks = Rank::ALL.to_vec();
+    if flipped {
+        files.reverse();
+    } else {
+        ranks.reverse();
+    }
+    let has_moved = !game.history().is_empty();
+
+    for rank in &ranks {
+        text.push_str(&format!("{} ", rank.to_char()));
+        for file in &files {
+            let square = Square::new(*file, *rank);
+            let c = match game.get_board_state().get(&square) {
+                Some(piece) => piece_char(piece, ascii),
+                None if ascii => '.',
+                None if !square.is_light() => '·',
+                None => ' ',
+            };
+            let highlight = has_moved && (Some(square) == game.last_moved_from || Some(square) == game.last_moved_to);
+            if highlight {
+                text.push_str(&format!("[{}]", c));
+            } else {
+                text.push_str(&format!(" {} ", c));
+            }
+        }
+        text.push('\n');
+    }
+
+    text.push_str("  ");
+    for file in &files {
+        text.push_str(&format!(" {} ", file.to_char()));
+    }
+    text.push('\n');
+
+    // the pockets in Crazyhouse
+    if game.variant() == Variant::Crazyhouse {
+        for (color, pocket) in [(PieceColor::White, &game.white_pocket), (PieceColor::Black, &game.black_pocket)] {
+            let pieces: String = pocket.iter().map(|piece_type| piece_char(&Piece { piece_type: *piece_type, color, pos: Square::A1, has_moved: true }, ascii)).collect();
+            text.push_str(&format!("{} pocket: {}\n", color_name(color), pieces));
+        }
+    }
+
+    text
+}
+
+fn piece_char(piece: &Piece, ascii: bool) -> char {
+    let (letter, white, black) = match piece.piece_type {
+        PieceType::King => ('K', '♔', '♚'),
+        PieceType::Queen => ('Q', '♕', '♛'),
+        PieceType::Rook => ('R', '♖', '♜'),
+        PieceType::Bishop => ('B', '♗', '♝'),
+        PieceType::Knight => ('N', '♘', '♞'),
+        PieceType::Pawn => ('P', '♙', '♟'),
+    };
+    match (piece.color, ascii) {
+        (PieceColor::White, true) => letter,
+        (PieceColor::Black, true) => letter.to_ascii_lowercase(),
+        (PieceColor::White, false) => white,
+        (PieceColor::Black, false) => black,
+    }
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+fn result_text(game: &Game) -> String {
+    let result = match game.result {
+        ChessResult::WhiteWon => "white wins",
+        ChessResult::BlackWon => "black wins",
+        ChessResult::Draw => "draw",
+        ChessResult::Ongoing => "ongoing",
+    };
+    let reason = match game.termination() {
+        Some(Termination::Checkmate) => "by checkmate",
+        Some(Termination::Stalemate) => "by stalemate",
+        Some(Termination::Repetition) => "by repetition",
+        Some(Termination::FiftyMoves) => "by the fifty-move rule",
+        Some(Termination::InsufficientMaterial) => "by insufficient material",
+        Some(Termination::VariantRule) => "by the rules of the variant",
+        Some(Termination::Declared) => "by agreement or resignation",
+        Some(Termination::TimeForfeit) => "on time",
+        None => "",
+    };
+    format!("{} {}", result, reason)
+}
+
+// writes the game as PGN, the moves are replayed from the start to write them in SAN
+fn pgn(game: &Game) -> String {
+    let result = match game.result {
+        ChessResult::WhiteWon => "1-0",
+        ChessResult::BlackWon => "0-1",
+        ChessResult::Draw => "1/2-1/2",
+        ChessResult::Ongoing => "*",
+    };
+
+    let mut text = String::new();
+    text.push_str(&format!("[Result \"{}\"]\n", result));
+    if game.variant() != Variant::Standard {
+        text.push_str(&format!("[Variant \"{}\"]\n", game.variant().name()));
+    }
+    let start = Game::from_fen_variant(game.start_fen(), game.variant());
+    if game.start_fen() != Game::new_variant(game.variant()).to_fen() {
+        text.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", game.start_fen()));
+    }
+    text.push('\n');
+
+    let mut replay = start;
+    replay.chess960 = game.chess960;
+    let mut moves = Vec::new();
+    for (i, m) in game.history().iter().enumerate() {
+        if replay.turn == PieceColor::White {
+            moves.push(format!("{}.", replay.fullmoves));
+        } else if i == 0 {
+            moves.push(format!("{}...", replay.fullmoves));
+        }
+        moves.push(replay.move_to_san(m).unwrap_or(m.to_uci()));
+        replay.make_move(m);
+    }
+    moves.push(result.to_string());
+    text.push_str(&moves.join(" "));
+
+    text
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} is not a valid number", value))
+}
@@ -0,0 +1,149 @@
+// Runs the engine on an EPD test suite and reports how many positions it solves.
+//
+// usage: epdtest <file.epd> [--movetime <ms>] [--depth <n>] [--threads <n>] [--hash <mb>]
+//
+// A position is solved if the engine plays one of the bm moves and none of the am moves.
+// Lines that are empty or start with # are skipped. Without limits each position gets one second.
+// Positions with a bm or am move that is not legal are not searched and are listed as errors.
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, Instant};
+
+use oscae_chess::epd::Epd;
+use oscae_chess::search::{Engine, SearchLimits};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        eprintln!("usage: epdtest <file.epd> [--movetime <ms>] [--depth <n>] [--threads <n>] [--hash <mb>]");
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut path = None;
+    let mut limits = SearchLimits::default();
+    let mut engine = Engine::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).ok_or(format!("{} needs a value", args[i]));
+        match args[i].as_str() {
+            "--movetime" => limits.movetime = Some(Duration::from_millis(parse(value()?)?)),
+            "--depth" => limits.depth = Some(parse(value()?)?),
+            "--threads" => engine.set_option("Threads", value()?)?,
+            "--hash" => engine.set_option("Hash", value()?)?,
+            arg if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            arg => {
+                path = Some(arg.to_string());
+                i += 1;
+                continue;
+            },
+        }
+        i += 2;
+    }
+
+    let path = path.ok_or("no EPD file given")?;
+    if limits.depth.is_none() && limits.movetime.is_none() {
+        limits.movetime = Some(Duration::from_secs(1));
+    }
+
+    let text = fs::read_to_string(&path).map_err(|e| format!("could not read {}: {}", path, e))?;
+
+    let mut solved = 0;
+    let mut total = 0;
+    let mut failures = Vec::new();
+    let mut errors = Vec::new();
+    let start = Instant::now();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let epd = Epd::from_line(line).map_err(|e| format!("line {}: {}", line_number + 1, e))?;
+        let id = epd.get_string("id").map(|id| id.to_string()).unwrap_or(format!("line {}", line_number + 1));
+
+        // get_moves leaves out moves that are not legal, which would make the position unsolvable
+        let illegal: Vec<&str> = ["bm", "am"].iter()
+            .filter_map(|opcode| epd.get(opcode))
+            .flatten()
+            .filter(|san| epd.game.move_from_san(san).is_none())
+            .map(|san| san.as_str())
+            .collect();
+        if !illegal.is_empty() {
+            println!("{:<20} error, not legal: {}", id, illegal.join(" "));
+            errors.push(format!("{}: not legal: {}", id, illegal.join(" ")));
+            continue;
+        }
+
+        engine.new_game();
+        let position_start = Instant::now();
+        let result = engine.search(&epd.game, &limits);
+        let time = position_start.elapsed();
+
+        let played = match result.best_move {
+            Some(m) => epd.game.move_to_san(&m).unwrap_or(m.to_uci()),
+            None => "none".to_string(),
+        };
+        let ok = match result.best_move {
+            Some(m) => match epd.is_solved_by(&m) {
+                Some(ok) => ok,
+                None => {
+                    println!("{:<20} skipped, no bm or am", id);
+                    continue;
+                },
+            },
+            None => false,
+        };
+
+        total += 1;
+        let expected = expected(&epd);
+        if ok {
+            solved += 1;
+        } else {
+            failures.push(format!("{}: played {}, expected {}", id, played, expected));
+        }
+        println!("{:<20} {:<4} {:<8} expected {:<16} depth {:>2} score {:>6} {:>6} ms",
+            id, if ok { "ok" } else { "FAIL" }, played, expected, result.depth, result.score, time.as_millis());
+    }
+
+    let elapsed = start.elapsed();
+    println!();
+    println!("solved {} of {} ({}%) in {:.1} s", solved, total, if total > 0 { solved * 100 / total } else { 0 }, elapsed.as_secs_f64());
+    if !failures.is_empty() {
+        println!("failed:");
+        for failure in failures {
+            println!("  {}", failure);
+        }
+    }
+    if !errors.is_empty() {
+        println!("errors ({} positions not searched):", errors.len());
+        for error in errors {
+            println!("  {}", error);
+        }
+    }
+
+    Ok(())
+}
+
+// the bm and am moves as written in the file
+fn expected(epd: &Epd) -> String {
+    let mut expected = Vec::new();
+    if let Some(moves) = epd.get("bm") {
+        expected.push(moves.join(" "));
+    }
+    if let Some(moves) = epd.get("am") {
+        expected.push(format!("not {}", moves.join(" ")));
+    }
+    expected.join(", ")
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} is not a valid number", value))
+}
@@ -0,0 +1,580 @@
+// Builds games from custom starting positions, such as odds games or a position set up piece by piece.
+//
+// The position is turned into FEN and read with Game::from_fen_variant(), so the castling rights and has_moved
+// are set the same way as for any other game. Unless set_castling() is used, a king and a rook that are
+// still on their starting squares can castle, in Chess960 the outermost rooks on the back rank.
+
+use std::fmt;
+
+use crate::variant::Variant;
+use crate::{Game, PieceColor, PieceType, Square};
+
+// the reasons build() can fail
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionError {
+    // the color does not have exactly one king (none for white in Horde, any number in Antichess)
+    KingCount(PieceColor),
+    // a pawn is on the first or last rank
+    PawnOnBackRank(Square),
+    // the side that is not to move is in check
+    OppositeCheck,
+    // a castling right is set but the king or the rook is not on a square it can castle from
+    CastlingRights,
+    // there is no pawn that could just have moved two steps past the en passant square
+    EnPassant,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::KingCount(color) => write!(f, "{} has the wrong number of kings", color_name(*color)),
+            PositionError::PawnOnBackRank(square) => write!(f, "there is a pawn on {}", square.to_notation()),
+            PositionError::OppositeCheck => write!(f, "the side that is not to move is in check"),
+            PositionError::CastlingRights => write!(f, "castling rights without a king and rook that can castle"),
+            PositionError::EnPassant => write!(f, "no pawn can be taken en passant"),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+// handicaps where the stronger player starts without some material
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Odds {
+    // without the f-pawn
+    Pawn,
+    // without the f-pawn and the other player moves first
+    PawnAndMove,
+    // without the queen's knight
+    Knight,
+    // without the queen's rook, castling long is then not possible
+    Rook,
+    Queen,
+}
+
+impl Odds {
+    // returns the piece that is removed, the one closest to the a-file when there are two (the queen's knight or rook)
+    fn piece_type(&self) -> PieceType {
+        match self {
+            Odds::Pawn | Odds::PawnAndMove => PieceType::Pawn,
+            Odds::Knight => PieceType::Knight,
+            Odds::Rook => PieceType::Rook,
+            Odds::Queen => PieceType::Queen,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    // indexed like Square::to_index()
+    pieces: [Option<(PieceType, PieceColor)>; 64],
+    turn: PieceColor,
+    // the file of the rook for [white short, white long, black short, black long], None means every possible right
+    castling: Option<[Option<i8>; 4]>,
+    en_passant: Option<Square>,
+    halfmoves: u32,
+    fullmoves: u32,
+    variant: Variant,
+    chess960: bool,
+    // Crazyhouse pockets and the bitmap of promoted pieces
+    white_pocket: Vec<PieceType>,
+    black_pocket: Vec<PieceType>,
+    promoted: u64,
+    // checks given by white and black in Three-check
+    checks: (u32, u32),
+}
+
+impl BoardBuilder {
+    // starts from the standard starting position with white to move
+    pub fn new() -> Self {
+        let mut builder = Self::empty();
+        let back_rank = [PieceType::Rook, PieceType::Knight, PieceType::Bishop, PieceType::Queen, PieceType::King, PieceType::Bishop, PieceType::Knight, PieceType::Rook];
+        for (x, piece_type) in back_rank.iter().enumerate() {
+            let x = x as i8;
+            builder.set_piece(Square::at(x, 0), *piece_type, PieceColor::White);
+            builder.set_piece(Square::at(x, 1), PieceType::Pawn, PieceColor::White);
+            builder.set_piece(Square::at(x, 6), PieceType::Pawn, PieceColor::Black);
+            builder.set_piece(Square::at(x, 7), *piece_type, PieceColor::Black);
+        }
+        builder
+    }
+
+    // starts from an empty board with white to move
+    pub fn empty() -> Self {
+        Self {
+            pieces: [None; 64],
+            turn: PieceColor::White,
+            castling: None,
+            en_passant: None,
+            halfmoves: 0,
+            fullmoves: 1,
+            variant: Variant::Standard,
+            chess960: false,
+            white_pocket: Vec::new(),
+            black_pocket: Vec::new(),
+            promoted: 0,
+            checks: (0, 0),
+        }
+    }
+
+    // starts from the position of a game, useful for editing it
+    pub fn from_game(game: &Game) -> Self {
+        let mut builder = Self::empty();
+        for piece in game.live_pieces.values() {
+            builder.set_piece(piece.pos, piece.piece_type, piece.color);
+        }
+        builder.turn = game.turn;
+
+        let mut castling = [None; 4];
+        for (i, color) in [PieceColor::White, PieceColor::Black].into_iter().enumerate() {
+            if let Some(king) = game.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == color) {
+                castling[i * 2] = game.castling_rook(king, true).map(|rook| rook.x);
+                castling[i * 2 + 1] = game.castling_rook(king, false).map(|rook| rook.x);
+            }
+        }
+        builder.castling = Some(castling);
+
+        builder.en_passant = game.en_passant_square();
+        builder.halfmoves = game.fifty_move_rule;
+        builder.fullmoves = game.fullmoves;
+        builder.variant = game.variant;
+        builder.chess960 = game.chess960;
+        builder.white_pocket = game.white_pocket.clone();
+        builder.black_pocket = game.black_pocket.clone();
+        builder.promoted = game.promoted;
+        builder.checks = (game.white_checks, game.black_checks);
+        builder
+    }
+
+    // returns the piece on a square
+    pub fn get_piece(&self, square: Square) -> Option<(PieceType, PieceColor)> {
+        if square.to_bitmap() == 0 {
+            return None;
+        }
+        self.pieces[square.to_index() as usize]
+    }
+
+    // places a piece, replacing whatever was on the square. squares outside the board are ignored
+    pub fn set_piece(&mut self, square: Square, piece_type: PieceType, color: PieceColor) -> &mut Self {
+        if square.to_bitmap() != 0 {
+            self.pieces[square.to_index() as usize] = Some((piece_type, color));
+            self.promoted &= !square.to_bitmap();
+        }
+        self
+    }
+
+    pub fn remove_piece(&mut self, square: Square) -> &mut Self {
+        if square.to_bitmap() != 0 {
+            self.pieces[square.to_index() as usize] = None;
+            self.promoted &= !square.to_bitmap();
+        }
+        self
+    }
+
+    // removes all pieces, castling and en passant
+    pub fn clear(&mut self) -> &mut Self {
+        self.pieces = [None; 64];
+        self.promoted = 0;
+        self.castling = None;
+        self.en_passant = None;
+        self
+    }
+
+    pub fn set_turn(&mut self, turn: PieceColor) -> &mut Self {
+        self.turn = turn;
+        self
+    }
+
+    // sets if the color may castle short and long. before it is used every possible castling is allowed
+    // the right is for the outermost rook on that side of the king, in normal chess the rook on the a- or h-file
+    pub fn set_castling(&mut self, color: PieceColor, short: bool, long: bool) -> &mut Self {
+        let possible = self.possible_castling();
+        let mut castling = self.castling.unwrap_or(possible);
+        let i = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => 2,
+        };
+        // without a rook the right is still set, build() then tells that it is not possible
+        castling[i] = if short { Some(possible[i].unwrap_or(7)) } else { None };
+        castling[i + 1] = if long { Some(possible[i + 1].unwrap_or(0)) } else { None };
+        self.castling = Some(castling);
+        self
+    }
+
+    // sets the square behind a pawn that just moved two steps (as in FEN), None if there is none
+    pub fn set_en_passant(&mut self, square: Option<Square>) -> &mut Self {
+        self.en_passant = square;
+        self
+    }
+
+    // sets the half-moves since the last capture or pawn move, 0 by default
+    pub fn set_halfmoves(&mut self, halfmoves: u32) -> &mut Self {
+        self.halfmoves = halfmoves;
+        self
+    }
+
+    // sets the fullmove number, 1 by default
+    pub fn set_fullmoves(&mut self, fullmoves: u32) -> &mut Self {
+        self.fullmoves = fullmoves;
+        self
+    }
+
+    // sets the rules the game is played with, Standard by default
+    pub fn set_variant(&mut self, variant: Variant) -> &mut Self {
+        self.variant = variant;
+        self
+    }
+
+    // turns on Chess960 castling, the king and the rooks can then castle from any file
+    pub fn set_chess960(&mut self, chess960: bool) -> &mut Self {
+        self.chess960 = chess960;
+        self
+    }
+
+    // sets the pieces the color can drop in Crazyhouse
+    pub fn set_pocket(&mut self, color: PieceColor, pieces: &[PieceType]) -> &mut Self {
+        match color {
+            PieceColor::White => self.white_pocket = pieces.to_vec(),
+            PieceColor::Black => self.black_pocket = pieces.to_vec(),
+        }
+        self
+    }
+
+    // sets the number of checks each color has given in Three-check
+    pub fn set_checks(&mut self, white: u32, black: u32) -> &mut Self {
+        self.checks = (white, black);
+        self
+    }
+
+    // removes the material of the odds from the color that gives them, pawn and move also lets the other color start
+    // the pieces are looked for on the back rank so it works for Chess960 positions as well, the f-pawn is always taken
+    pub fn give_odds(&mut self, odds: Odds, color: PieceColor) -> &mut Self {
+        let (y, pawn_y) = match color {
+            PieceColor::White => (0, 1),
+            PieceColor::Black => (7, 6),
+        };
+        let square = match odds.piece_type() {
+            PieceType::Pawn => Some(Square::at(5, pawn_y)),
+            piece_type => (0..8).map(|x| Square::at(x, y)).find(|square| self.get_piece(*square) == Some((piece_type, color))),
+        };
+
+        if let Some(square) = square {
+            self.remove_piece(square);
+            // the other rook keeps its castling right
+            if let Some(castling) = &mut self.castling {
+                let first = if color == PieceColor::White { 0 } else { 2 };
+                for right in &mut castling[first..first + 2] {
+                    if *right == Some(square.x) {
+                        *right = None;
+                    }
+                }
+            }
+        }
+
+        if odds == Odds::PawnAndMove {
+            self.turn = !color;
+        }
+        self
+    }
+
+    // returns the position as FEN, castling is allowed for every king and rook on their starting squares
+    // the castling rights are written as X-FEN, KQkq unless a rook that can not castle is further out
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for y in (0..8).rev() {
+            let mut empty = 0;
+            for x in 0..8 {
+                match self.pieces[(y * 8 + x) as usize] {
+                    Some((piece_type, color)) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_char(piece_type, color));
+                        if self.promoted & Square::at(x, y).to_bitmap() != 0 {
+                            fen.push('~');
+                        }
+                    },
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if y > 0 {
+                fen.push('/');
+            }
+        }
+
+        if self.variant == Variant::Crazyhouse {
+            fen.push('[');
+            fen.extend(self.white_pocket.iter().map(|p| piece_char(*p, PieceColor::White)));
+            fen.extend(self.black_pocket.iter().map(|p| piece_char(*p, PieceColor::Black)));
+            fen.push(']');
+        }
+
+        fen.push_str(if self.turn == PieceColor::White { " w " } else { " b " });
+
+        let mut castling = String::new();
+        let rights = self.castling.unwrap_or_else(|| self.possible_castling());
+        for (i, right) in rights.iter().enumerate() {
+            let rook_x = match right {
+                Some(x) => *x,
+                None => continue,
+            };
+            let (color, y) = if i < 2 { (PieceColor::White, 0) } else { (PieceColor::Black, 7) };
+            let short = i % 2 == 0;
+            let outer_files = if short { rook_x + 1..8 } else { 0..rook_x };
+            let outermost = !outer_files.into_iter().any(|x| self.get_piece(Square::at(x, y)) == Some((PieceType::Rook, color)));
+            let c = match (outermost, short) {
+                (true, true) => 'K',
+                (true, false) => 'Q',
+                (false, _) => (b'A' + rook_x as u8) as char,
+            };
+            castling.push(if color == PieceColor::White { c } else { c.to_ascii_lowercase() });
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        fen.push_str(&castling);
+        fen.push(' ');
+
+        match self.en_passant {
+            Some(square) => fen.push_str(&square.to_notation().to_ascii_lowercase()),
+            None => fen.push('-'),
+        }
+        fen.push_str(&format!(" {} {}", self.halfmoves, self.fullmoves));
+
+        if self.variant == Variant::ThreeCheck {
+            fen.push_str(&format!(" +{}+{}", self.checks.0, self.checks.1));
+        }
+        fen
+    }
+
+    // returns the file of the king on the back rank of the color
+    fn king_file(&self, color: PieceColor) -> Option<i8> {
+        let y = if color == PieceColor::White { 0 } else { 7 };
+        (0..8).find(|x| self.get_piece(Square::at(*x, y)) == Some((PieceType::King, color)))
+    }
+
+    // returns the rook files of every possible castling: the rooks on the a- and h-files with the king on the e-file, in
+    // Chess960 the outermost rook on each side of the king
+    fn possible_castling(&self) -> [Option<i8>; 4] {
+        let mut rights = [None; 4];
+        if !self.variant.has_castling() {
+            return rights;
+        }
+        for (i, (color, y)) in [(PieceColor::White, 0), (PieceColor::Black, 7)].into_iter().enumerate() {
+            let king_x = match self.king_file(color) {
+                Some(x) => x,
+                None => continue,
+            };
+            let is_rook = |x: &i8| self.get_piece(Square::at(*x, y)) == Some((PieceType::Rook, color));
+            if self.chess960 {
+                rights[i * 2] = (king_x + 1..8).rev().find(is_rook);
+                rights[i * 2 + 1] = (0..king_x).find(is_rook);
+            } else if king_x == 4 {
+                rights[i * 2] = Some(7).filter(is_rook);
+                rights[i * 2 + 1] = Some(0).filter(is_rook);
+            }
+        }
+        rights
+    }
+
+    // creates the game, returns an error if the position is not legal
+    pub fn build(&self) -> Result<Game, PositionError> {
+        for color in [PieceColor::White, PieceColor::Black] {
+            // the king is an ordinary piece in Antichess and the white pieces in Horde have none
+            let kings = match (self.variant, color) {
+                (Variant::Antichess, _) => continue,
+                (Variant::Horde, PieceColor::White) => 0,
+                _ => 1,
+            };
+            if self.pieces.iter().filter(|p| **p == Some((PieceType::King, color))).count() != kings {
+                return Err(PositionError::KingCount(color));
+            }
+        }
+
+        for x in 0..8 {
+            for y in [0, 7] {
+                let square = Square::at(x, y);
+                let horde_pawn = self.variant == Variant::Horde && y == 0;
+                if matches!(self.get_piece(square), Some((PieceType::Pawn, _))) && !horde_pawn {
+                    return Err(PositionError::PawnOnBackRank(square));
+                }
+            }
+        }
+
+        // every right needs a rook on the side of the king it castles to, on the a- or h-file unless it is Chess960
+        if let Some(castling) = self.castling {
+            for (i, right) in castling.iter().enumerate() {
+                let rook_x = match right {
+                    Some(x) => *x,
+                    None => continue,
+                };
+                let (color, y) = if i < 2 { (PieceColor::White, 0) } else { (PieceColor::Black, 7) };
+                let short = i % 2 == 0;
+                let valid = self.variant.has_castling() && match self.king_file(color) {
+                    Some(king_x) if self.chess960 => if short { rook_x > king_x } else { rook_x < king_x },
+                    Some(king_x) => king_x == 4 && rook_x == if short { 7 } else { 0 },
+                    None => false,
+                } && self.get_piece(Square::at(rook_x, y)) == Some((PieceType::Rook, color));
+                if !valid {
+                    return Err(PositionError::CastlingRights);
+                }
+            }
+        }
+
+        // the pawn is in front of the square, and the square and the one behind it are empty
+        if let Some(square) = self.en_passant {
+            let (y, direction) = match self.turn {
+                PieceColor::White => (5, -1),
+                PieceColor::Black => (2, 1),
+            };
+            let valid = square.to_bitmap() != 0 && square.y == y
+                && self.get_piece(square.moved(0, direction)) == Some((PieceType::Pawn, !self.turn))
+                && self.get_piece(square).is_none()
+                && self.get_piece(square.moved(0, -direction)).is_none();
+            if !valid {
+                return Err(PositionError::EnPassant);
+            }
+        }
+
+        // the player who just moved can not have left the king in check
+        let mut other = self.clone();
+        other.set_turn(!self.turn).set_en_passant(None);
+        if Game::from_fen_variant(&other.to_fen(), self.variant).check {
+            return Err(PositionError::OppositeCheck);
+        }
+
+        let mut game = Game::from_fen_variant(&self.to_fen(), self.variant);
+        game.chess960 = self.chess960;
+        Ok(game)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn piece_char(piece_type: PieceType, color: PieceColor) -> char {
+    let c = piece_type.to_char();
+    if color == PieceColor::White { c } else { c.to_ascii_lowercase() }
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "White",
+        PieceColor::Black => "Black",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odds() {
+        assert_eq!(BoardBuilder::new().build().unwrap().to_fen(), Game::new().to_fen());
+
+        // the remaining rook can still castle
+        let game = BoardBuilder::new().give_odds(Odds::Rook, PieceColor::White).build().unwrap();
+        assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1");
+        let game = BoardBuilder::new().give_odds(Odds::Queen, PieceColor::Black).build().unwrap();
+        assert_eq!(game.to_fen(), "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let game = BoardBuilder::new().give_odds(Odds::PawnAndMove, PieceColor::White).build().unwrap();
+        assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPP1PP/RNBQKBNR b KQkq - 0 1");
+        let game = BoardBuilder::new().give_odds(Odds::Knight, PieceColor::White).build().unwrap();
+        assert_eq!(game.get_all_moves().len(), 19);
+
+        // Chess960 starting with BBQNNRKR, the rook on f1 is the queen's rook and h1 can still castle
+        let chess960 = Game::new_chess960(0).unwrap();
+        let game = BoardBuilder::from_game(&chess960).give_odds(Odds::Rook, PieceColor::White).build().unwrap();
+        assert!(game.chess960);
+        assert_eq!(game.to_fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNN1KR w Kkq - 0 1");
+        assert_eq!(game.castling_rights(), [true, false, true, true]);
+        let game = BoardBuilder::from_game(&chess960).give_odds(Odds::Knight, PieceColor::Black).build().unwrap();
+        assert_eq!(game.to_fen(), "bbq1nrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+        let game = BoardBuilder::from_game(&chess960).give_odds(Odds::Queen, PieceColor::White).build().unwrap();
+        assert_eq!(game.to_fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BB1NNRKR w KQkq - 0 1");
+    }
+
+    #[test]
+    fn test_custom_position() {
+        let mut builder = BoardBuilder::empty();
+        builder.set_piece(Square::E1, PieceType::King, PieceColor::White)
+            .set_piece(Square::H1, PieceType::Rook, PieceColor::White)
+            .set_piece(Square::E8, PieceType::King, PieceColor::Black);
+        let mut game = builder.build().unwrap();
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(game.do_move(&Square::E1, &Square::G1));
+
+        // kings are required and the side not to move can not be in check
+        builder.set_piece(Square::E4, PieceType::Rook, PieceColor::White);
+        assert_eq!(builder.build().err(), Some(PositionError::OppositeCheck));
+        builder.set_turn(PieceColor::Black);
+        assert!(builder.build().is_ok());
+        assert_eq!(BoardBuilder::empty().build().err(), Some(PositionError::KingCount(PieceColor::White)));
+    }
+
+    #[test]
+    fn test_editor() {
+        let mut game = Game::new();
+        assert!(game.do_move(&Square::E2, &Square::E4));
+        let mut builder = BoardBuilder::from_game(&game);
+        assert_eq!(builder.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+        // castling rights can be taken away but not given without the pieces
+        builder.set_castling(PieceColor::White, false, true);
+        assert_eq!(builder.build().unwrap().to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b Qkq e3 0 1");
+        builder.remove_piece(Square::A1);
+        assert_eq!(builder.build().err(), Some(PositionError::CastlingRights));
+        builder.set_castling(PieceColor::White, false, false);
+
+        // the en passant square needs a pawn in front of it
+        builder.set_en_passant(Some(Square::D3));
+        assert_eq!(builder.build().err(), Some(PositionError::EnPassant));
+        builder.set_en_passant(None).set_piece(Square::H8, PieceType::Pawn, PieceColor::Black);
+        assert_eq!(builder.build().err(), Some(PositionError::PawnOnBackRank(Square::H8)));
+
+        builder.clear();
+        assert!(builder.get_piece(Square::E1).is_none() && builder.to_fen() == "8/8/8/8/8/8/8/8 b - - 0 1");
+    }
+
+    #[test]
+    fn test_from_game() {
+        // the move counters are kept and can be set
+        let fen = "r3k2r/pppq1ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPPQ1PPP/R3K2R b KQkq - 3 17";
+        let mut builder = BoardBuilder::from_game(&Game::from_fen(fen));
+        assert_eq!(builder.build().unwrap().to_fen(), fen);
+        builder.set_halfmoves(0).set_fullmoves(1);
+        assert_eq!(builder.to_fen(), "r3k2r/pppq1ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPPQ1PPP/R3K2R b KQkq - 0 1");
+
+        // Chess960 castling from other files
+        let game = Game::new_chess960(0).unwrap();
+        let mut builder = BoardBuilder::from_game(&game);
+        let built = builder.build().unwrap();
+        assert!(built.chess960);
+        assert_eq!(built.to_fen(), game.to_fen());
+        assert_eq!(built.get_all_moves(), game.get_all_moves());
+        builder.set_chess960(false);
+        assert_eq!(builder.build().err(), Some(PositionError::CastlingRights));
+
+        // the variant, the pockets, promoted pieces and the checks
+        let fen = "rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/Q~NBQKBNR[Pn] w Kkq - 0 5";
+        let game = Game::from_fen_variant(fen, Variant::Crazyhouse);
+        assert_eq!(BoardBuilder::from_game(&game).build().unwrap().to_fen(), game.to_fen());
+        let game = Game::from_fen_variant("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +2+1", Variant::ThreeCheck);
+        assert_eq!(BoardBuilder::from_game(&game).build().unwrap().to_fen(), game.to_fen());
+        let game = Game::new_variant(Variant::Horde);
+        assert_eq!(BoardBuilder::from_game(&game).build().unwrap().to_fen(), game.to_fen());
+        let game = Game::new_variant(Variant::Antichess);
+        assert_eq!(BoardBuilder::from_game(&game).build().unwrap().to_fen(), game.to_fen());
+
+        let mut builder = BoardBuilder::new();
+        builder.set_variant(Variant::Crazyhouse).set_pocket(PieceColor::Black, &[PieceType::Queen]);
+        assert_eq!(builder.build().unwrap().black_pocket, vec![PieceType::Queen]);
+    }
+}
@@ -0,0 +1,271 @@
+// Compact binary encoding of whole games for storage.
+//
+// Every move is stored as its index in get_all_moves(), which always lists the moves in the same order, so most
+// moves take a single byte. Positions with more than 256 legal moves (possible in Crazyhouse) use two bytes.
+//
+// layout, numbers are LEB128 varints unless written otherwise:
+//  "OCG" and the version (1 byte)
+//  variant (1 byte), see variant::VARIANTS
+//  flags (1 byte): 1 = the start position is written, otherwise it is the starting position of the variant
+//                  2 = the last move is waiting for pawn_promotion()
+//                  4 = Chess960 castling, the start position does not always tell
+//  result (1 byte): 0 ongoing, 1 white won, 2 black won, 3 draw
+//  termination (1 byte, since version 2): 0 none, otherwise the index in TERMINATIONS + 1
+//  start FEN (length and bytes) if flag 1 is set
+//  number of tags, then each name and value (length and bytes)
+//  number of moves, then each move (1 or 2 bytes, little endian)
+
+use crate::variant::VARIANTS;
+use crate::{ChessResult, Game, Move, PieceType, Termination};
+
+const MAGIC: &[u8] = b"OCG";
+const VERSION: u8 = 2;
+
+const RESULTS: [ChessResult; 4] = [ChessResult::Ongoing, ChessResult::WhiteWon, ChessResult::BlackWon, ChessResult::Draw];
+// the order must never change
+const TERMINATIONS: [Termination; 8] = [
+    Termination::Checkmate, Termination::Stalemate, Termination::Repetition, Termination::FiftyMoves,
+    Termination::InsufficientMaterial, Termination::VariantRule, Termination::Declared, Termination::TimeForfeit,
+];
+
+const FLAG_START_FEN: u8 = 1;
+const FLAG_PENDING_PROMOTION: u8 = 2;
+const FLAG_CHESS960: u8 = 4;
+
+impl Game {
+    // encodes the game from its start position with tags such as ("White", "Magnus"), see the top of encoding.rs
+    // fails if a move in the history can not be replayed from the start position
+    pub fn encode(&self, tags: &[(String, String)]) -> Result<Vec<u8>, String> {
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.push(VARIANTS.iter().position(|v| *v == self.variant).unwrap_or(0) as u8);
+
+        let standard_start = Game::new_variant(self.variant).to_fen() == self.start_fen;
+        let mut flags = 0;
+        if !standard_start {
+            flags |= FLAG_START_FEN;
+        }
+        if self.promotion {
+            flags |= FLAG_PENDING_PROMOTION;
+        }
+        if self.chess960 {
+            flags |= FLAG_CHESS960;
+        }
+        data.push(flags);
+        data.push(RESULTS.iter().position(|r| *r == self.result).unwrap_or(0) as u8);
+        data.push(match self.termination {
+            Some(termination) => TERMINATIONS.iter().position(|t| *t == termination).unwrap_or(0) as u8 + 1,
+            None => 0,
+        });
+
+        if !standard_start {
+            write_bytes(&mut data, self.start_fen.as_bytes());
+        }
+
+        write_varint(&mut data, tags.len() as u64);
+        for (name, value) in tags {
+            write_bytes(&mut data, name.as_bytes());
+            write_bytes(&mut data, value.as_bytes());
+        }
+
+        write_varint(&mut data, self.history.len() as u64);
+        let mut game = Game::from_fen_variant(&self.start_fen, self.variant);
+        game.chess960 = self.chess960;
+        for (i, m) in self.history.iter().enumerate() {
+            let moves = game.get_all_moves();
+            // a move waiting for the promotion is stored as the queen promotion
+            let pending = self.promotion && i + 1 == self.history.len();
+            let stored = if pending { Move { promotion: Some(PieceType::Queen), ..*m } } else { *m };
+            let index = moves.iter().position(|legal| *legal == stored)
+                .ok_or(format!("move {} ({}) is not legal", i + 1, m.to_uci()))?;
+
+            data.push(index as u8);
+            if moves.len() > 256 {
+                data.push((index >> 8) as u8);
+            }
+
+            if pending {
+                game.do_move(&m.from, &m.to);
+            } else {
+                game.make_move(m);
+            }
+        }
+
+        Ok(data)
+    }
+
+    // decodes a game written by encode(), returns the game and its tags
+    pub fn decode(data: &[u8]) -> Result<(Game, Vec<(String, String)>), String> {
+        let mut reader = Reader { data, pos: 0 };
+
+        if reader.take(3)? != MAGIC {
+            return Err("not an encoded game".to_string());
+        }
+        // version 1 is the same without the termination
+        let version = reader.byte()?;
+        if version != 1 && version != VERSION {
+            return Err(format!("unknown version {}", version));
+        }
+
+        let variant = *VARIANTS.get(reader.byte()? as usize).ok_or("unknown variant")?;
+        let flags = reader.byte()?;
+        let result = *RESULTS.get(reader.byte()? as usize).ok_or("unknown result")?;
+        let termination = match if version == 1 { 0 } else { reader.byte()? } {
+            0 => None,
+            i => Some(*TERMINATIONS.get(i as usize - 1).ok_or("unknown termination")?),
+        };
+
+        let mut game = if flags & FLAG_START_FEN != 0 {
+            Game::from_fen_variant(&reader.string()?, variant)
+        } else {
+            Game::new_variant(variant)
+        };
+        game.chess960 |= flags & FLAG_CHESS960 != 0;
+
+        let tag_count = reader.varint()?;
+        let mut tags = Vec::new();
+        for _ in 0..tag_count {
+            tags.push((reader.string()?, reader.string()?));
+        }
+
+        let move_count = reader.varint()?;
+        for i in 0..move_count {
+            let moves = game.get_all_moves();
+            let mut index = reader.byte()? as usize;
+            if moves.len() > 256 {
+                index |= (reader.byte()? as usize) << 8;
+            }
+            let m = *moves.get(index).ok_or(format!("move {} is not legal", i + 1))?;
+
+            if flags & FLAG_PENDING_PROMOTION != 0 && i + 1 == move_count {
+                game.do_move(&m.from, &m.to);
+            } else {
+                game.make_move(&m);
+            }
+        }
+
+        if game.result == ChessResult::Ongoing && result != ChessResult::Ongoing {
+            game.result = result;
+            game.termination = Some(termination.unwrap_or(Termination::Declared));
+        }
+
+        Ok((game, tags))
+    }
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn write_bytes(data: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(data, bytes.len() as u64);
+    data.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        if self.pos + n > self.data.len() {
+            return Err("the data ends too early".to_string());
+        }
+        self.pos += n;
+        Ok(&self.data[self.pos - n..self.pos])
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("a number is too large".to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.varint()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "a string is not UTF-8".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::Variant;
+    use crate::{PieceColor, Square};
+
+    #[test]
+    fn test_encoding() {
+        let mut game = Game::new();
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5c6", "d7c6", "e1g1"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let tags = vec![("White".to_string(), "Carlsen".to_string()), ("Event".to_string(), "Ruy López".to_string())];
+        let data = game.encode(&tags).unwrap();
+        // the header and one byte per move
+        assert_eq!(data.len(), 8 + 1 + 6 + 8 + 6 + 11 + 1 + 9);
+
+        let (decoded, decoded_tags) = Game::decode(&data).unwrap();
+        assert!(decoded.to_fen() == game.to_fen() && decoded.history() == game.history());
+        assert_eq!(decoded_tags, tags);
+
+        // other start positions, a declared result and a pending promotion
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(game.do_move(&Square::B7, &Square::B8));
+        let (decoded, _) = Game::decode(&game.encode(&[]).unwrap()).unwrap();
+        assert!(decoded.promotion && decoded.to_fen() == game.to_fen());
+        let mut game = Game::new_chess960(0).unwrap();
+        game.declare_win(PieceColor::White);
+        let (decoded, _) = Game::decode(&game.encode(&[]).unwrap()).unwrap();
+        assert!(decoded.chess960 && decoded.result == ChessResult::WhiteWon);
+        assert_eq!(decoded.termination(), Some(Termination::Declared));
+
+        // a game lost on time, and the same game written by version 1 without the termination
+        let mut game = Game::new();
+        game.flag(PieceColor::White);
+        let mut data = game.encode(&[]).unwrap();
+        let (decoded, _) = Game::decode(&data).unwrap();
+        assert_eq!(decoded.result, ChessResult::BlackWon);
+        assert_eq!(decoded.termination(), Some(Termination::TimeForfeit));
+        data[3] = 1;
+        data.remove(7);
+        assert_eq!(Game::decode(&data).unwrap().0.termination(), Some(Termination::Declared));
+
+        // Chess960 castling from the usual start position, the king takes its own rook
+        let mut game = Game::new_chess960(518).unwrap();
+        for uci in ["e2e4", "e7e5", "g1f3", "g8f6", "f1e2", "f8e7", "e1h1"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let (decoded, _) = Game::decode(&game.encode(&[]).unwrap()).unwrap();
+        assert!(decoded.chess960 && decoded.to_fen() == game.to_fen() && decoded.history() == game.history());
+
+        // more than 256 legal moves
+        let mut game = Game::from_fen_variant("4k3/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1", Variant::Crazyhouse);
+        assert!(game.get_all_moves().len() > 256);
+        assert!(game.do_drop(PieceType::Pawn, &Square::H7));
+        let (decoded, _) = Game::decode(&game.encode(&[]).unwrap()).unwrap();
+        assert_eq!(decoded.to_fen(), game.to_fen());
+
+        // a move that can not be replayed is an error instead of being stored as another move
+        let mut game = Game::new();
+        game.history.push(Move::from_uci("e2e5").unwrap());
+        assert!(game.encode(&[]).is_err());
+
+        assert!(Game::decode(b"PGN").is_err());
+        assert!(Game::decode(&data[..data.len() - 3]).is_err());
+    }
+}
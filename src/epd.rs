@@ -0,0 +1,252 @@
+// Extended Position Description (EPD), used by test suites such as WAC and STS.
+//
+// A line is the first four fields of FEN followed by operations, an opcode and its operands ending with ';':
+//  2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+// Moves in bm, am and pv are written in SAN for the position. Operands with spaces are quoted.
+
+use crate::{Game, Move};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    pub opcode: String,
+    // without quotes
+    pub operands: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Epd {
+    pub game: Game,
+    // in the order they are written
+    pub operations: Vec<Operation>,
+}
+
+impl Epd {
+    // creates an EPD with no operations
+    pub fn new(game: Game) -> Self {
+        Self { game, operations: Vec::new() }
+    }
+
+    // reads an EPD line, the halfmove clock and fullmove number are taken from hmvc and fmvn if they are there
+    // the position fields are checked the same way as when a FEN is parsed into a Game
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let mut fields = Vec::new();
+        let mut rest = line;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("expected four position fields in \"{}\"", line));
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        let operations = parse_operations(rest)?;
+
+        let mut epd = Self { game: Game::new(), operations };
+        let halfmoves = epd.get_number("hmvc").unwrap_or(0);
+        let fullmoves = epd.get_number("fmvn").unwrap_or(1);
+        epd.game = format!("{} {} {}", fields.join(" "), halfmoves, fullmoves).parse()?;
+        Ok(epd)
+    }
+
+    // returns the EPD line, the position is written without the move counters
+    pub fn to_line(&self) -> String {
+        let fen = self.game.to_fen();
+        let mut line = fen.split_whitespace().take(4).collect::<Vec<&str>>().join(" ");
+
+        for operation in &self.operations {
+            line.push(' ');
+            line.push_str(&operation.opcode);
+            for operand in &operation.operands {
+                line.push(' ');
+                if is_string_opcode(&operation.opcode) || operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';') {
+                    line.push('"');
+                    line.push_str(operand);
+                    line.push('"');
+                } else {
+                    line.push_str(operand);
+                }
+            }
+            line.push(';');
+        }
+
+        line
+    }
+
+    // returns the operands of the opcode
+    pub fn get(&self, opcode: &str) -> Option<&Vec<String>> {
+        self.operations.iter().find(|o| o.opcode == opcode).map(|o| &o.operands)
+    }
+
+    // sets the operands of the opcode, replacing the old ones or adding it last
+    pub fn set(&mut self, opcode: &str, operands: Vec<String>) {
+        match self.operations.iter_mut().find(|o| o.opcode == opcode) {
+            Some(operation) => operation.operands = operands,
+            None => self.operations.push(Operation { opcode: opcode.to_string(), operands }),
+        }
+    }
+
+    pub fn remove(&mut self, opcode: &str) {
+        self.operations.retain(|o| o.opcode != opcode);
+    }
+
+    // returns the first operand as a string, used for id and c0-c9
+    pub fn get_string(&self, opcode: &str) -> Option<&str> {
+        self.get(opcode).and_then(|operands| operands.first()).map(|s| s.as_str())
+    }
+
+    // returns the first operand as a number, used for acd (depth), ce (centipawn evaluation), hmvc and fmvn
+    pub fn get_number(&self, opcode: &str) -> Option<i64> {
+        self.get_string(opcode).and_then(|s| s.parse().ok())
+    }
+
+    // returns the moves of an opcode with SAN operands such as bm or am, moves that are not legal are left out
+    pub fn get_moves(&self, opcode: &str) -> Vec<Move> {
+        match self.get(opcode) {
+            Some(operands) => operands.iter().filter_map(|san| self.game.move_from_san(san)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // sets the operands of an opcode to moves written in SAN, moves that are not legal are left out
+    pub fn set_moves(&mut self, opcode: &str, moves: &[Move]) {
+        let operands = moves.iter().filter_map(|m| self.game.move_to_san(m)).collect();
+        self.set(opcode, operands);
+    }
+
+    // returns true if the move is one of the best moves (bm) and none of the moves to avoid (am)
+    // None if the position has neither, so there is nothing to check the move against
+    pub fn is_solved_by(&self, m: &Move) -> Option<bool> {
+        if self.get("bm").is_none() && self.get("am").is_none() {
+            return None;
+        }
+        let best = self.get("bm").is_none() || self.get_moves("bm").contains(m);
+        let avoided = self.get_moves("am").contains(m);
+        Some(best && !avoided)
+    }
+
+    // returns the principal variation, each move is played before the next is read. stops at the first illegal move
+    pub fn get_pv(&self) -> Vec<Move> {
+        let mut game = self.game.clone();
+        let mut pv = Vec::new();
+        for san in self.get("pv").into_iter().flatten() {
+            match game.move_from_san(san) {
+                Some(m) if game.make_move(&m) => pv.push(m),
+                _ => break,
+            }
+        }
+        pv
+    }
+
+    // sets the principal variation, stops at the first illegal move
+    pub fn set_pv(&mut self, pv: &[Move]) {
+        let mut game = self.game.clone();
+        let mut operands = Vec::new();
+        for m in pv {
+            match game.move_to_san(m) {
+                Some(san) if game.make_move(m) => operands.push(san),
+                _ => break,
+            }
+        }
+        self.set("pv", operands);
+    }
+}
+
+// id and the comments c0-c9 are always quoted
+fn is_string_opcode(opcode: &str) -> bool {
+    opcode == "id" || (opcode.len() == 2 && opcode.starts_with('c') && opcode.as_bytes()[1].is_ascii_digit())
+}
+
+fn parse_operations(text: &str) -> Result<Vec<Operation>, String> {
+    let mut operations = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    operations.push(Operation { opcode, operands: std::mem::take(&mut tokens) });
+                }
+            },
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("a quoted operand is not closed".to_string()),
+                    }
+                }
+                if tokens.is_empty() {
+                    return Err(format!("operand \"{}\" has no opcode", token));
+                }
+                tokens.push(token);
+            },
+            c if c.is_whitespace() => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() || *c == ';' || *c == '"' {
+                        break;
+                    }
+                    token.push(*c);
+                    chars.next();
+                }
+                tokens.push(token);
+            },
+        }
+    }
+
+    // the last operation may leave out the ';'
+    if !tokens.is_empty() {
+        let opcode = tokens.remove(0);
+        operations.push(Operation { opcode, operands: tokens });
+    }
+
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epd() {
+        let line = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+        let epd = Epd::from_line(line).unwrap();
+        assert_eq!(epd.to_line(), line);
+        assert_eq!(epd.get_string("id"), Some("WAC.001"));
+        assert_eq!(epd.get_moves("bm"), vec![Move::from_uci("g3g6").unwrap()]);
+        assert_eq!(epd.is_solved_by(&Move::from_uci("g3g6").unwrap()), Some(true));
+        assert_eq!(epd.is_solved_by(&Move::from_uci("g3g4").unwrap()), Some(false));
+
+        // quoted operands keep their spaces and semicolons, the counters come from hmvc and fmvn
+        let mut epd = Epd::from_line("4k3/8/8/8/8/8/8/4K2R w K - am O-O Rh8+; c0 \"one; two\"; acd 12; ce -35; hmvc 7; fmvn 30").unwrap();
+        assert_eq!(epd.game.to_fen(), "4k3/8/8/8/8/8/8/4K2R w K - 7 30");
+        assert_eq!(epd.get_moves("am").len(), 2);
+        assert_eq!(epd.is_solved_by(&Move::from_uci("h1h8").unwrap()), Some(false));
+        assert_eq!(epd.is_solved_by(&Move::from_uci("h1h7").unwrap()), Some(true));
+        assert_eq!(epd.get_string("c0"), Some("one; two"));
+        assert!(epd.get_number("acd") == Some(12) && epd.get_number("ce") == Some(-35));
+
+        epd.set_pv(&[Move::from_uci("h1h8").unwrap(), Move::from_uci("e8d7").unwrap(), Move::from_uci("a1a2").unwrap()]);
+        assert_eq!(epd.get("pv").unwrap(), &vec!["Rh8+".to_string(), "Kd7".to_string()]);
+        assert_eq!(epd.get_pv().len(), 2);
+        epd.remove("hmvc");
+        epd.remove("fmvn");
+        epd.remove("am");
+        assert_eq!(epd.to_line(), "4k3/8/8/8/8/8/8/4K2R w K - c0 \"one; two\"; acd 12; ce -35; pv Rh8+ Kd7;");
+
+        assert!(Epd::from_line("4k3/8/8/8/8/8/8/4K2R w").is_err());
+        assert!(Epd::from_line("4k3/8/8/8/8/8/8/4K2R w - - id \"open").is_err());
+
+        // the position fields are checked like a FEN
+        assert!(Epd::from_line("zz9/8 w - - bm e4;").is_err());
+        assert!(Epd::from_line("4k3/8/8/8/8/8/8/4K2R x - - bm Rh8;").is_err());
+        assert!(Epd::from_line("4k3/8/8/8/8/8/8/4K2R w K e9 bm Rh8;").is_err());
+    }
+}
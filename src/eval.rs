@@ -0,0 +1,561 @@
+// Static evaluation of a position.
+//
+// The score is in centipawns and is seen from the side to move, positive is good for the player whose turn it is.
+// Every term is calculated twice, once for the middlegame (mg) and once for the endgame (eg).
+// The two are blended depending on how much material is left on the board (tapered evaluation).
+//
+// All weights live in EvalParams so they can be tweaked without recompiling, see EvalParams::load().
+// Arrays with one value per piece are indexed in the same order as PieceType: King, Queen, Bishop, Knight, Rook, Pawn.
+// Piece-square tables are written from whites point of view with A8 first and H1 last, just as the board looks when printed.
+
+use std::fs;
+
+use crate::{Game, Piece, PieceColor, PieceType, Square};
+
+// phase value of each piece type, a full board adds up to MAX_PHASE
+const PHASE_VALUES: [i32; 6] = [0, 4, 1, 1, 2, 0];
+const MAX_PHASE: i32 = 24;
+
+const PIECE_NAMES: [&str; 6] = ["king", "queen", "bishop", "knight", "rook", "pawn"];
+
+#[rustfmt::skip]
+const PST_KING_MG: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const PST_KING_EG: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+#[rustfmt::skip]
+const PST_QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const PST_BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const PST_KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const PST_ROOK: [i32; 64] = [
+      0,  0,  0,  0,  0,  0,  0,  0,
+      5, 10, 10, 10, 10, 10, 10,  5,
+     -5,  0,  0,  0,  0,  0,  0, -5,
+     -5,  0,  0,  0,  0,  0,  0, -5,
+     -5,  0,  0,  0,  0,  0,  0, -5,
+     -5,  0,  0,  0,  0,  0,  0, -5,
+     -5,  0,  0,  0,  0,  0,  0, -5,
+      0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PST_PAWN_MG: [i32; 64] = [
+      0,  0,  0,  0,  0,  0,  0,  0,
+     50, 50, 50, 50, 50, 50, 50, 50,
+     10, 10, 20, 30, 30, 20, 10, 10,
+      5,  5, 10, 25, 25, 10,  5,  5,
+      0,  0,  0, 20, 20,  0,  0,  0,
+      5, -5,-10,  0,  0,-10, -5,  5,
+      5, 10, 10,-20,-20, 10, 10,  5,
+      0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PST_PAWN_EG: [i32; 64] = [
+      0,  0,  0,  0,  0,  0,  0,  0,
+     80, 80, 80, 80, 80, 80, 80, 80,
+     50, 50, 50, 50, 50, 50, 50, 50,
+     30, 30, 30, 30, 30, 30, 30, 30,
+     20, 20, 20, 20, 20, 20, 20, 20,
+     10, 10, 10, 10, 10, 10, 10, 10,
+      0,  0,  0,  0,  0,  0,  0,  0,
+      0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+// all weights used by evaluate()
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalParams {
+    // value of each piece type
+    pub material_mg: [i32; 6],
+    pub material_eg: [i32; 6],
+
+    // bonus for standing on a square, one table per piece type
+    pub pst_mg: [[i32; 64]; 6],
+    pub pst_eg: [[i32; 64]; 6],
+
+    // pawn structure, penalties are negative
+    pub doubled_pawn_mg: i32,
+    pub doubled_pawn_eg: i32,
+    pub isolated_pawn_mg: i32,
+    pub isolated_pawn_eg: i32,
+    // bonus for a passed pawn, indexed by rank counted from the pawns own side (0-7)
+    pub passed_pawn_mg: [i32; 8],
+    pub passed_pawn_eg: [i32; 8],
+
+    // king safety, only used in the middlegame
+    // bonus for each own pawn right in front of the king
+    pub king_shield_mg: i32,
+    // penalty for each file next to the king without own pawns
+    pub king_open_file_mg: i32,
+    // penalty for each square around the king that an opponent piece attacks
+    pub king_attack_mg: i32,
+
+    // bonus per square a piece can move to
+    pub mobility_mg: [i32; 6],
+    pub mobility_eg: [i32; 6],
+
+    // bonus for having both bishops
+    pub bishop_pair_mg: i32,
+    pub bishop_pair_eg: i32,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            material_mg: [0, 900, 330, 320, 500, 100],
+            material_eg: [0, 950, 340, 300, 530, 120],
+            pst_mg: [PST_KING_MG, PST_QUEEN, PST_BISHOP, PST_KNIGHT, PST_ROOK, PST_PAWN_MG],
+            pst_eg: [PST_KING_EG, PST_QUEEN, PST_BISHOP, PST_KNIGHT, PST_ROOK, PST_PAWN_EG],
+            doubled_pawn_mg: -10,
+            doubled_pawn_eg: -20,
+            isolated_pawn_mg: -10,
+            isolated_pawn_eg: -15,
+            passed_pawn_mg: [0, 5, 10, 15, 25, 40, 60, 0],
+            passed_pawn_eg: [0, 10, 20, 35, 55, 80, 110, 0],
+            king_shield_mg: 10,
+            king_open_file_mg: -15,
+            king_attack_mg: -6,
+            mobility_mg: [0, 1, 4, 4, 2, 0],
+            mobility_eg: [0, 2, 4, 4, 4, 0],
+            bishop_pair_mg: 30,
+            bishop_pair_eg: 50,
+        }
+    }
+}
+
+impl EvalParams {
+    // reads parameters from a file, see parse() for the format
+    pub fn load(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) => Err(format!("could not read {}: {}", path, e)),
+        }
+    }
+
+    // writes all parameters to a file in the same format that load() reads
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("could not write {}: {}", path, e))
+    }
+
+    // parses parameters from text with one "name = values" per line, values separated by whitespace
+    // lines starting with # are comments. Parameters that are not mentioned keep their default value
+    // e.g. "material_mg = 0 900 330 320 500 100" or "bishop_pair_mg = 30" or "pst_mg_knight = <64 values>"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut params = Self::default();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, values) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => return Err(format!("line {}: expected \"name = values\"", line_number + 1)),
+            };
+
+            let mut numbers = Vec::new();
+            for value in values.split_whitespace() {
+                match value.parse::<i32>() {
+                    Ok(i) => numbers.push(i),
+                    Err(_) => return Err(format!("line {}: \"{}\" is not a number", line_number + 1, value)),
+                }
+            }
+
+            if let Err(e) = params.set(name.trim(), &numbers) {
+                return Err(format!("line {}: {}", line_number + 1, e));
+            }
+        }
+
+        Ok(params)
+    }
+
+    // returns all parameters as text that parse() can read
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for name in self.names() {
+            let values: Vec<String> = self.get(&name).iter().map(|v| v.to_string()).collect();
+            text.push_str(&format!("{} = {}\n", name, values.join(" ")));
+        }
+        text
+    }
+
+    // all parameter names, in the order they are written by to_text()
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = ["material_mg", "material_eg"].iter().map(|s| s.to_string()).collect();
+        for phase in ["mg", "eg"] {
+            for piece in PIECE_NAMES {
+                names.push(format!("pst_{}_{}", phase, piece));
+            }
+        }
+        for name in [
+            "doubled_pawn_mg", "doubled_pawn_eg", "isolated_pawn_mg", "isolated_pawn_eg", "passed_pawn_mg", "passed_pawn_eg",
+            "king_shield_mg", "king_open_file_mg", "king_attack_mg", "mobility_mg", "mobility_eg", "bishop_pair_mg", "bishop_pair_eg",
+        ] {
+            names.push(name.to_string());
+        }
+        names
+    }
+
+    fn get(&self, name: &str) -> Vec<i32> {
+        if let Some((phase, piece)) = pst_name(name) {
+            let tables = if phase == "mg" { &self.pst_mg } else { &self.pst_eg };
+            return tables[piece].to_vec();
+        }
+
+        match name {
+            "material_mg" => self.material_mg.to_vec(),
+            "material_eg" => self.material_eg.to_vec(),
+            "doubled_pawn_mg" => vec![self.doubled_pawn_mg],
+            "doubled_pawn_eg" => vec![self.doubled_pawn_eg],
+            "isolated_pawn_mg" => vec![self.isolated_pawn_mg],
+            "isolated_pawn_eg" => vec![self.isolated_pawn_eg],
+            "passed_pawn_mg" => self.passed_pawn_mg.to_vec(),
+            "passed_pawn_eg" => self.passed_pawn_eg.to_vec(),
+            "king_shield_mg" => vec![self.king_shield_mg],
+            "king_open_file_mg" => vec![self.king_open_file_mg],
+            "king_attack_mg" => vec![self.king_attack_mg],
+            "mobility_mg" => self.mobility_mg.to_vec(),
+            "mobility_eg" => self.mobility_eg.to_vec(),
+            "bishop_pair_mg" => vec![self.bishop_pair_mg],
+            "bishop_pair_eg" => vec![self.bishop_pair_eg],
+            _ => Vec::new(),
+        }
+    }
+
+    fn set(&mut self, name: &str, values: &[i32]) -> Result<(), String> {
+        if let Some((phase, piece)) = pst_name(name) {
+            let tables = if phase == "mg" { &mut self.pst_mg } else { &mut self.pst_eg };
+            return copy_values(&mut tables[piece], values);
+        }
+
+        match name {
+            "material_mg" => copy_values(&mut self.material_mg, values),
+            "material_eg" => copy_values(&mut self.material_eg, values),
+            "doubled_pawn_mg" => copy_values(std::slice::from_mut(&mut self.doubled_pawn_mg), values),
+            "doubled_pawn_eg" => copy_values(std::slice::from_mut(&mut self.doubled_pawn_eg), values),
+            "isolated_pawn_mg" => copy_values(std::slice::from_mut(&mut self.isolated_pawn_mg), values),
+            "isolated_pawn_eg" => copy_values(std::slice::from_mut(&mut self.isolated_pawn_eg), values),
+            "passed_pawn_mg" => copy_values(&mut self.passed_pawn_mg, values),
+            "passed_pawn_eg" => copy_values(&mut self.passed_pawn_eg, values),
+            "king_shield_mg" => copy_values(std::slice::from_mut(&mut self.king_shield_mg), values),
+            "king_open_file_mg" => copy_values(std::slice::from_mut(&mut self.king_open_file_mg), values),
+            "king_attack_mg" => copy_values(std::slice::from_mut(&mut self.king_attack_mg), values),
+            "mobility_mg" => copy_values(&mut self.mobility_mg, values),
+            "mobility_eg" => copy_values(&mut self.mobility_eg, values),
+            "bishop_pair_mg" => copy_values(std::slice::from_mut(&mut self.bishop_pair_mg), values),
+            "bishop_pair_eg" => copy_values(std::slice::from_mut(&mut self.bishop_pair_eg), values),
+            _ => Err(format!("unknown parameter \"{}\"", name)),
+        }
+    }
+}
+
+// splits a name like "pst_mg_knight" into ("mg", piece index)
+fn pst_name(name: &str) -> Option<(&str, usize)> {
+    let rest = name.strip_prefix("pst_")?;
+    let (phase, piece) = rest.split_once('_')?;
+    if phase != "mg" && phase != "eg" {
+        return None;
+    }
+    PIECE_NAMES.iter().position(|p| *p == piece).map(|i| (phase, i))
+}
+
+fn copy_values(target: &mut [i32], values: &[i32]) -> Result<(), String> {
+    if target.len() != values.len() {
+        return Err(format!("expected {} values but got {}", target.len(), values.len()));
+    }
+    target.copy_from_slice(values);
+    Ok(())
+}
+
+// index into a piece-square table, the tables are seen from white so black squares are mirrored
+fn pst_index(piece: &Piece) -> usize {
+    let y = match piece.color {
+        PieceColor::White => 7 - piece.pos.y,
+        PieceColor::Black => piece.pos.y,
+    };
+    (y * 8 + piece.pos.x) as usize
+}
+
+// returns the rank of a square counted from the side of color, 0 is the back rank
+fn relative_rank(square: Square, color: PieceColor) -> i8 {
+    match color {
+        PieceColor::White => square.y,
+        PieceColor::Black => 7 - square.y,
+    }
+}
+
+// evaluates the position with the default parameters, see evaluate_with()
+pub fn evaluate(game: &Game) -> i32 {
+    evaluate_with(game, &EvalParams::default())
+}
+
+// returns the static evaluation of the position in centipawns from the point of view of the side to move
+pub fn evaluate_with(game: &Game, params: &EvalParams) -> i32 {
+    // white minus black
+    let mut mg = 0;
+    let mut eg = 0;
+    let mut phase = 0;
+
+    // pawns on each file, used for pawn structure and king safety
+    let mut white_pawn_files = [0; 8];
+    let mut black_pawn_files = [0; 8];
+    for piece in game.live_pieces.values().filter(|p| p.piece_type == PieceType::Pawn) {
+        match piece.color {
+            PieceColor::White => white_pawn_files[piece.pos.x as usize] += 1,
+            PieceColor::Black => black_pawn_files[piece.pos.x as usize] += 1,
+        }
+    }
+
+    let mut white_bishops = 0;
+    let mut black_bishops = 0;
+
+    for piece in game.live_pieces.values() {
+        let index = piece.piece_type.index();
+        let sign = match piece.color {
+            PieceColor::White => 1,
+            PieceColor::Black => -1,
+        };
+
+        phase += PHASE_VALUES[index];
+
+        // material and piece-square tables
+        mg += sign * (params.material_mg[index] + params.pst_mg[index][pst_index(piece)]);
+        eg += sign * (params.material_eg[index] + params.pst_eg[index][pst_index(piece)]);
+
+        // mobility
+        if piece.piece_type != PieceType::Pawn && piece.piece_type != PieceType::King {
+            let (own_color_bitmap, other_color_bitmap) = match piece.color {
+                PieceColor::White => (game.white_bitmap, game.black_bitmap),
+                PieceColor::Black => (game.black_bitmap, game.white_bitmap),
+            };
+            let moves = game.psuedo_legal_moves(piece, own_color_bitmap, other_color_bitmap).count_ones() as i32;
+            mg += sign * moves * params.mobility_mg[index];
+            eg += sign * moves * params.mobility_eg[index];
+        }
+
+        match piece.piece_type {
+            PieceType::Bishop => match piece.color {
+                PieceColor::White => white_bishops += 1,
+                PieceColor::Black => black_bishops += 1,
+            },
+            PieceType::Pawn => {
+                let (own_files, other_files) = match piece.color {
+                    PieceColor::White => (&white_pawn_files, &black_pawn_files),
+                    PieceColor::Black => (&black_pawn_files, &white_pawn_files),
+                };
+                let (mg_pawn, eg_pawn) = pawn_structure(game, piece, own_files, other_files, params);
+                mg += sign * mg_pawn;
+                eg += sign * eg_pawn;
+            },
+            PieceType::King => {
+                mg += sign * king_safety(game, piece, params);
+            },
+            _ => (),
+        }
+    }
+
+    // bishop pair
+    if white_bishops >= 2 {
+        mg += params.bishop_pair_mg;
+        eg += params.bishop_pair_eg;
+    }
+    if black_bishops >= 2 {
+        mg -= params.bishop_pair_mg;
+        eg -= params.bishop_pair_eg;
+    }
+
+    // blend middlegame and endgame depending on the material left
+    let phase = phase.min(MAX_PHASE);
+    let score = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE;
+
+    match game.turn {
+        PieceColor::White => score,
+        PieceColor::Black => -score,
+    }
+}
+
+// returns (mg, eg) for doubled, isolated and passed pawns, from the point of view of the pawns color
+fn pawn_structure(game: &Game, pawn: &Piece, own_files: &[i32; 8], other_files: &[i32; 8], params: &EvalParams) -> (i32, i32) {
+    let x = pawn.pos.x as usize;
+    let mut mg = 0;
+    let mut eg = 0;
+
+    // doubled, only the pawns behind the most advanced one are counted
+    if own_files[x] > 1 {
+        let is_front_pawn = (1..8).all(|dy| {
+            match game.live_pieces.get(&pawn.pos.moved(0, dy * pawn.get_direction())) {
+                Some(other) => other.piece_type != PieceType::Pawn || other.color != pawn.color,
+                None => true,
+            }
+        });
+        if !is_front_pawn {
+            mg += params.doubled_pawn_mg;
+            eg += params.doubled_pawn_eg;
+        }
+    }
+
+    // isolated
+    let left = if x > 0 { own_files[x - 1] } else { 0 };
+    let right = if x < 7 { own_files[x + 1] } else { 0 };
+    if left == 0 && right == 0 {
+        mg += params.isolated_pawn_mg;
+        eg += params.isolated_pawn_eg;
+    }
+
+    // passed, no opponent pawns in front on the same or neighbouring files
+    let has_neighbouring_opponent = (x.saturating_sub(1)..=(x + 1).min(7)).any(|file| other_files[file] > 0);
+    let passed = !has_neighbouring_opponent || !game.live_pieces.values().any(|other| {
+        other.piece_type == PieceType::Pawn &&
+            other.color != pawn.color &&
+            (other.pos.x - pawn.pos.x).abs() <= 1 &&
+            relative_rank(other.pos, pawn.color) > relative_rank(pawn.pos, pawn.color)
+    });
+    if passed {
+        let rank = relative_rank(pawn.pos, pawn.color) as usize;
+        mg += params.passed_pawn_mg[rank];
+        eg += params.passed_pawn_eg[rank];
+    }
+
+    (mg, eg)
+}
+
+// returns the middlegame king safety score from the point of view of the kings color
+fn king_safety(game: &Game, king: &Piece, params: &EvalParams) -> i32 {
+    let mut score = 0;
+    let direction = king.get_direction();
+
+    for dx in -1..=1 {
+        let file = king.pos.x + dx;
+        if !(0..8).contains(&file) {
+            continue;
+        }
+
+        // pawn shield, one or two squares in front of the king
+        let shield = (1..=2).any(|dy| match game.live_pieces.get(&king.pos.moved(dx, dy * direction)) {
+            Some(p) => p.piece_type == PieceType::Pawn && p.color == king.color,
+            None => false,
+        });
+        if shield {
+            score += params.king_shield_mg;
+        }
+
+        // files without own pawns
+        let has_own_pawn = game.live_pieces.values().any(|p| p.piece_type == PieceType::Pawn && p.color == king.color && p.pos.x == file);
+        if !has_own_pawn {
+            score += params.king_open_file_mg;
+        }
+    }
+
+    // squares around the king attacked by the opponent
+    let king_zone = game.psuedo_legal_moves_king(king, 0);
+    let (own_color_bitmap, other_color_bitmap) = match king.color {
+        PieceColor::White => (game.black_bitmap, game.white_bitmap),
+        PieceColor::Black => (game.white_bitmap, game.black_bitmap),
+    };
+    let mut attacked = 0;
+    for attacker in game.live_pieces.values().filter(|p| p.color != king.color && p.piece_type != PieceType::King) {
+        attacked |= game.psuedo_legal_moves(attacker, own_color_bitmap, other_color_bitmap) & king_zone;
+    }
+    score += attacked.count_ones() as i32 * params.king_attack_mg;
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_symmetry() {
+        // the starting position is equal for both sides
+        let game = Game::new();
+        assert_eq!(evaluate(&game), 0);
+
+        // mirrored positions get the same score for the side to move
+        let white = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let black = Game::from_fen("rnbqkb1r/pppp1ppp/5n2/4p3/4P3/2N5/PPPP1PPP/R1BQKBNR b KQkq - 2 3");
+        assert_eq!(evaluate(&white), evaluate(&black));
+    }
+
+    #[test]
+    fn test_evaluate_terms() {
+        // white is a queen up
+        let game = Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(evaluate(&game) > 800);
+        let game = Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+        assert!(evaluate(&game) < -800);
+
+        // a passed pawn on the seventh is worth more than one on the second
+        let far = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        let near = Game::from_fen("4k3/8/8/8/8/8/P7/4K3 w - - 0 1");
+        assert!(evaluate(&far) > evaluate(&near));
+    }
+
+    #[test]
+    fn test_params() {
+        let params = EvalParams::default();
+        assert_eq!(EvalParams::parse(&params.to_text()), Ok(params.clone()));
+
+        let tweaked = EvalParams::parse("# coach settings\nbishop_pair_mg = 100\nmaterial_eg = 0 1000 300 300 500 100\n").unwrap();
+        assert_eq!(tweaked.bishop_pair_mg, 100);
+        assert_eq!(tweaked.material_eg[1], 1000);
+        assert_eq!(tweaked.bishop_pair_eg, params.bishop_pair_eg);
+
+        assert!(EvalParams::parse("bishop_pair_mg = 1 2").is_err());
+        assert!(EvalParams::parse("no_such_thing = 1").is_err());
+        assert!(EvalParams::parse("mobility_mg = a b c").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+// Events for UIs, sounds and networking, so they can react to what happened instead of comparing capture, check,
+// promotion, last_moved_* and result after every call.
+//
+// Nothing is recorded until set_events(true) is called, then every change is queued in order until take_events().
+// A clone of a game never records events, that way the search and move_to_san() do not pay for them.
+//
+// A move that promotes gives PromotionRequired first and MoveMade once pawn_promotion() is called. After MoveMade
+// come Check and GameEnded if the move led to them.
+
+use crate::animation::Animation;
+use crate::{ChessResult, Game, Move, PieceColor, PieceType, Square, Termination};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    MoveMade {
+        m: Move,
+        // with the check or mate sign
+        san: String,
+        color: PieceColor,
+        capture: bool,
+        castle: bool,
+        en_passant: bool,
+        promotion: Option<PieceType>,
+        // see move_animation()
+        animation: Vec<Animation>,
+    },
+    // the color is in check
+    Check(PieceColor),
+    GameEnded(ChessResult, Termination),
+    // a pawn reached the square, pawn_promotion() has to be called
+    PromotionRequired(Square),
+    DrawOffered(PieceColor),
+    // the color ran out of time
+    ClockFlagged(PieceColor),
+}
+
+// what has to be known about a move before it is made
+pub(crate) struct MoveEvent {
+    // without the promotion and the check sign
+    san: String,
+    castle: bool,
+    en_passant: bool,
+    animation: Vec<Animation>,
+}
+
+#[derive(Default)]
+pub(crate) struct Events {
+    enabled: bool,
+    queue: Vec<GameEvent>,
+    // a move waiting for pawn_promotion()
+    pending: Option<MoveEvent>,
+}
+
+impl Clone for Events {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Game {
+    // turns recording events on or off, turning it off drops the events that were not taken
+    pub fn set_events(&mut self, enabled: bool) {
+        self.events = Events { enabled, ..Events::default() };
+    }
+
+    // returns the events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events.queue)
+    }
+
+    // tells the other side that the color offers a draw, accept it with declare_draw()
+    pub fn offer_draw(&mut self, color: PieceColor) {
+        if self.result == ChessResult::Ongoing {
+            self.push_event(GameEvent::DrawOffered(color));
+        }
+    }
+
+    // ends the game because the color ran out of time, the other color wins
+    pub fn flag(&mut self, color: PieceColor) {
+        if self.result != ChessResult::Ongoing {
+            return;
+        }
+        self.result = match color {
+            PieceColor::White => ChessResult::BlackWon,
+            PieceColor::Black => ChessResult::WhiteWon,
+        };
+        self.termination = Some(Termination::TimeForfeit);
+        self.push_event(GameEvent::ClockFlagged(color));
+        self.game_ended_event();
+    }
+
+    fn push_event(&mut self, event: GameEvent) {
+        if self.events.enabled {
+            self.events.queue.push(event);
+        }
+    }
+
+    // called before a legal move is made, None if events are off
+    pub(crate) fn before_move_event(&self, m: &Move) -> Option<MoveEvent> {
+        if !self.events.enabled {
+            return None;
+        }
+        if m.drop.is_some() {
+            return Some(MoveEvent { san: self.san_without_suffix(m)?, castle: false, en_passant: false, animation: self.move_animation(m)? });
+        }
+
+        let piece = self.live_pieces.get(&m.from)?;
+        let castle = piece.piece_type == PieceType::King && self.castling_rook_for_move(piece, m.to).is_some();
+        let en_passant = piece.piece_type == PieceType::Pawn && m.from.x != m.to.x && !self.live_pieces.contains_key(&m.to);
+
+        // a promotion is written with the queen and the piece is put in once it is chosen
+        let promotes = piece.piece_type == PieceType::Pawn && (m.to.y == 0 || m.to.y == 7);
+        let m = if promotes { Move::with_promotion(m.from, m.to, PieceType::Queen) } else { *m };
+        let mut san = self.san_without_suffix(&m)?;
+        if promotes {
+            san.truncate(san.len() - 2);
+        }
+
+        Some(MoveEvent { san, castle, en_passant, animation: self.move_animation(&m)? })
+    }
+
+    // called after a move is made with what before_move_event() returned
+    pub(crate) fn after_move_event(&mut self, event: Option<MoveEvent>) {
+        let event = match event {
+            Some(event) => event,
+            None => return,
+        };
+
+        if self.promotion {
+            self.events.pending = Some(event);
+            if let Some(square) = self.last_moved_to {
+                self.push_event(GameEvent::PromotionRequired(square));
+            }
+            return;
+        }
+
+        let m = match self.history.last() {
+            Some(m) => *m,
+            None => return,
+        };
+        let mut san = event.san;
+        let mut animation = event.animation;
+        if let Some(promotion) = m.promotion {
+            san.push('=');
+            san.push(promotion.to_char());
+            for step in animation.iter_mut() {
+                if let Animation::Transformation { piece_type, .. } = step {
+                    *piece_type = promotion;
+                }
+            }
+        }
+        if self.check {
+            let mate = self.termination == Some(Termination::Checkmate);
+            san.push(if mate { '#' } else { '+' });
+        }
+
+        self.push_event(GameEvent::MoveMade {
+            m,
+            san,
+            color: !self.turn,
+            capture: self.capture,
+            castle: event.castle,
+            en_passant: event.en_passant,
+            promotion: m.promotion,
+            animation,
+        });
+        if self.check {
+            self.push_event(GameEvent::Check(self.turn));
+        }
+        self.game_ended_event();
+    }
+
+    // called after pawn_promotion()
+    pub(crate) fn after_promotion_event(&mut self) {
+        let event = self.events.pending.take();
+        self.after_move_event(event);
+    }
+
+    pub(crate) fn game_ended_event(&mut self) {
+        if let (ChessResult::WhiteWon | ChessResult::BlackWon | ChessResult::Draw, Some(termination)) = (self.result, self.termination) {
+            self.push_event(GameEvent::GameEnded(self.result, termination));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let mut game = Game::new();
+        assert!(game.make_move(&Move::from_uci("e2e4").unwrap()));
+        assert!(game.take_events().is_empty());
+
+        game.set_events(true);
+        for uci in ["f7f6", "d2d4", "g7g5"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        game.take_events();
+        assert!(game.make_move(&Move::from_uci("d1h5").unwrap()));
+        let events = game.take_events();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], GameEvent::MoveMade { san, color: PieceColor::White, capture: false, .. } if san == "Qh5#"));
+        assert_eq!(events[1], GameEvent::Check(PieceColor::Black));
+        assert_eq!(events[2], GameEvent::GameEnded(ChessResult::WhiteWon, Termination::Checkmate));
+
+        // castling, en passant and promotion
+        let mut game = Game::from_fen("4k3/1P6/8/3pP3/8/8/8/4K2R w K d6 0 1");
+        game.set_events(true);
+        assert!(game.make_move(&Move::from_uci("e5d6").unwrap()));
+        assert!(matches!(&game.take_events()[0], GameEvent::MoveMade { san, capture: true, en_passant: true, .. } if san == "exd6"));
+        assert!(game.make_move(&Move::from_uci("e8d7").unwrap()));
+        assert!(game.make_move(&Move::from_uci("e1g1").unwrap()));
+        assert!(matches!(&game.take_events()[1], GameEvent::MoveMade { san, castle: true, .. } if san == "O-O"));
+        assert!(game.make_move(&Move::from_uci("d7e6").unwrap()));
+        game.take_events();
+        assert!(game.do_move(&Square::B7, &Square::B8));
+        assert_eq!(game.take_events(), vec![GameEvent::PromotionRequired(Square::B8)]);
+        assert!(game.pawn_promotion(PieceType::Knight));
+        let events = game.take_events();
+        assert!(matches!(&events[0], GameEvent::MoveMade { san, promotion: Some(PieceType::Knight), animation, .. }
+            if san == "b8=N" && animation[1] == Animation::Transformation { square: Square::B8, piece_type: PieceType::Knight }));
+
+        game.offer_draw(PieceColor::Black);
+        game.flag(PieceColor::Black);
+        let events = game.take_events();
+        assert!(events == vec![GameEvent::DrawOffered(PieceColor::Black), GameEvent::ClockFlagged(PieceColor::Black),
+            GameEvent::GameEnded(ChessResult::WhiteWon, Termination::TimeForfeit)]);
+        assert_eq!(game.termination(), Some(Termination::TimeForfeit));
+
+        // clones do not record
+        let mut copy = Game::new();
+        copy.set_events(true);
+        let mut copy = copy.clone();
+        copy.declare_draw();
+        assert!(copy.take_events().is_empty() && copy.termination() == Some(Termination::Declared));
+    }
+}
@@ -10,7 +10,29 @@
 
 //use std::vec;
 
-use std::{collections::HashMap, ops::Not};
+use std::{collections::HashMap, fmt, ops::Not, str::FromStr};
+
+pub mod animation;
+pub mod builder;
+mod encoding;
+pub mod epd;
+pub mod eval;
+pub mod events;
+mod packed;
+mod san;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod search;
+pub mod strength;
+pub mod svg;
+pub mod tablebase;
+pub mod tree;
+pub mod tt;
+pub mod variant;
+mod zobrist;
+
+use events::Events;
+use variant::Variant;
 
 // DATA
 #[derive(Clone)]
@@ -25,11 +47,13 @@ pub struct Game {
     pub turn: PieceColor,
     // tells the result
     pub result: ChessResult,
+    // why the game ended, None while it is ongoing
+    termination: Option<Termination>,
     
     // used for en passant and for highlighting the squares that was just affected
-    // both are set to -1, -1 initially
-    pub last_moved_from: Square,
-    pub last_moved_to: Square,
+    // both are None before the first move
+    pub last_moved_from: Option<Square>,
+    pub last_moved_to: Option<Square>,
     
     // true if the last move was a capture
     pub capture: bool,
@@ -48,6 +72,29 @@ pub struct Game {
 
     // starts at 1 and is incremented when black moves
     pub fullmoves: u32,
+
+    // Chess960 (Fischer Random) castling rules, castling moves are written as the king taking its own rook
+    pub chess960: bool,
+
+    // the rules that are played, see the variant module
+    variant: Variant,
+
+    // number of checks given by each side, used by Three-check
+    pub white_checks: u32,
+    pub black_checks: u32,
+
+    // pieces that can be dropped in Crazyhouse, white_pocket holds the pieces white can drop
+    pub white_pocket: Vec<PieceType>,
+    pub black_pocket: Vec<PieceType>,
+    // bitmap of pieces that were promoted, they turn back into pawns when captured in Crazyhouse
+    promoted: u64,
+
+    // the position the game started from and the moves played since, used to save and replay games
+    start_fen: String,
+    history: Vec<Move>,
+
+    // events for UIs, see the events module
+    events: Events,
 }
 
 impl Game {
@@ -89,37 +136,133 @@ impl Game {
 
         let white_bitmap = 0b00000000_00000000_00000000_00000000_00000000_00000000_11111111_11111111;
         let black_bitmap = 0b11111111_11111111_00000000_00000000_00000000_00000000_00000000_00000000;
-        let last_moved_from = Square {x: -1, y: -1};
-        let last_moved_to = Square {x: -1, y: -1};
+        let last_moved_from = None;
+        let last_moved_to = None;
         let capture = false;
         let check = false;
         let promotion = false;
         let white_captured_pieces = Vec::new();
         let black_captured_pieces = Vec::new();
         let fullmoves = 1;
-        let mut game = Self {live_pieces, turn, result, fifty_move_rule, previous_states, white_bitmap, black_bitmap, last_moved_from, last_moved_to, capture, check, promotion, white_captured_pieces, black_captured_pieces, fullmoves};
+        let chess960 = false;
+        let variant = Variant::Standard;
+        let white_checks = 0;
+        let black_checks = 0;
+        let white_pocket = Vec::new();
+        let black_pocket = Vec::new();
+        let promoted = 0;
+        let start_fen = Variant::Standard.starting_fen().to_string();
+        let history = Vec::new();
+        let termination = None;
+        let events = Events::default();
+        let mut game = Self {live_pieces, turn, result, termination, fifty_move_rule, previous_states, white_bitmap, black_bitmap, last_moved_from, last_moved_to, capture, check, promotion, white_captured_pieces, black_captured_pieces, fullmoves, chess960, variant, white_checks, black_checks, white_pocket, black_pocket, promoted, start_fen, history, events};
         game.previous_states.insert(BoardValue::from(&game), 1);
 
         game
     }
 
+    // creates a new game of a variant with its starting position
+    pub fn new_variant(variant: Variant) -> Self {
+        Game::from_fen_variant(variant.starting_fen(), variant)
+    }
+
+    // creates a Chess960 starting position from its number 0-959, 518 is the normal starting position
+    // the numbering is the standard one (Scharnagl), bishops first, then the queen, the knights and the rest is R K R
+    pub fn new_chess960(index: u32) -> Result<Self, String> {
+        if index >= 960 {
+            return Err(format!("Chess960 positions are numbered 0-959, got {}", index));
+        }
+
+        let mut back_rank = [' '; 8];
+        let mut n = index;
+
+        // light squared bishop on b, d, f or h and dark squared bishop on a, c, e or g
+        back_rank[(n % 4 * 2 + 1) as usize] = 'B';
+        n /= 4;
+        back_rank[(n % 4 * 2) as usize] = 'B';
+        n /= 4;
+
+        // the queen on one of the 6 empty squares
+        let empty: Vec<usize> = (0..8).filter(|x| back_rank[*x] == ' ').collect();
+        back_rank[empty[(n % 6) as usize]] = 'Q';
+        n /= 6;
+
+        // the knights on two of the 5 empty squares
+        let knights = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n as usize];
+        let empty: Vec<usize> = (0..8).filter(|x| back_rank[*x] == ' ').collect();
+        back_rank[empty[knights.0]] = 'N';
+        back_rank[empty[knights.1]] = 'N';
+
+        // the king between the rooks
+        for (i, x) in (0..8).filter(|x| back_rank[*x] == ' ').collect::<Vec<usize>>().iter().enumerate() {
+            back_rank[*x] = if i == 1 { 'K' } else { 'R' };
+        }
+
+        let white: String = back_rank.iter().collect();
+        let mut game = Game::from_fen(&format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", white.to_ascii_lowercase(), white));
+        game.chess960 = true;
+        Ok(game)
+    }
+
     // creates a chess instance from FEN
-    pub fn from_fen(fen: &String) -> Self {
-        let mut game = Game::new();
+    // the castling field can be KQkq, X-FEN (KQkq for the outermost rook, file letters for other rooks)
+    // or Shredder-FEN (file letters only), chess960 is turned on if castling is not possible in normal chess
+    pub fn from_fen(fen: &str) -> Self {
+        Game::from_fen_variant(fen, Variant::Standard)
+    }
+
+    // creates a chess instance of a variant from FEN
+    // Three-check counters can be given as checks given "+W+B" after the fullmove number or as checks left "W+B" before
+    // the halfmove clock
+    pub fn from_fen_variant(fen: &str, variant: Variant) -> Self {
+        let mut game = Game::parse_fen(fen, variant);
+        game.start_fen = game.to_fen();
+        game
+    }
 
-        let fields: Vec<&str> = fen.trim().split_whitespace().collect();
+    fn parse_fen(fen: &str, variant: Variant) -> Self {
+        let mut game = Game::new();
+        game.variant = variant;
+
+        // the check counters are taken out so the other fields are in the normal places
+        let mut checks = (0, 0);
+        let fields: Vec<&str> = fen.split_whitespace().filter(|field| {
+            let counters: Vec<Option<u32>> = field.trim_start_matches('+').split('+').map(|n| n.parse().ok()).collect();
+            match counters[..] {
+                [Some(white), Some(black)] if field.starts_with('+') => checks = (white, black),
+                [Some(white), Some(black)] => checks = (3u32.saturating_sub(white), 3u32.saturating_sub(black)),
+                _ => return true,
+            }
+            false
+        }).collect();
         
         // 1 pieces
-        if fields.len() < 1 { return game; }
+        if fields.is_empty() { return game; }
         
         game.live_pieces.clear();
-
-        let template = Piece { piece_type: PieceType::Pawn, color: PieceColor::White, pos: Square {x: -1, y: -1}, has_moved: true};
+        // the starting position of Game::new() does not count for repetitions
+        game.previous_states.clear();
+
+        // Crazyhouse pockets are written in brackets after the pieces, or as a ninth rank
+        let (placement, pocket) = match fields[0].split_once('[') {
+            Some((placement, pocket)) => (placement, pocket.trim_end_matches(']')),
+            None if fields[0].matches('/').count() == 8 => fields[0].rsplit_once('/').unwrap_or((fields[0], "")),
+            None => (fields[0], ""),
+        };
+        for c in pocket.chars() {
+            if let Some(piece_type) = PieceType::from_char(c) {
+                if c.is_ascii_uppercase() {
+                    game.white_pocket.push(piece_type);
+                } else {
+                    game.black_pocket.push(piece_type);
+                }
+            }
+        }
 
         let mut x = 0;
         let mut y = 7;
 
-        for c in fields[0].chars() {
+        for c in placement.chars() {
             let mut piece_type = PieceType::Pawn;
             let color = if c.to_ascii_lowercase() == c {
                 PieceColor::Black
@@ -131,6 +274,8 @@ impl Game {
 
             match c.to_ascii_uppercase() {
                 '/' => { y -= 1; x = 0; continue; },
+                // the piece before was promoted (Crazyhouse)
+                '~' => { game.promoted |= Square::at(x - 1, y).to_bitmap(); continue; },
                 'K' => { piece_type = PieceType::King; has_moved = false },
                 'Q' => piece_type = PieceType::Queen,
                 'B' => piece_type = PieceType::Bishop,
@@ -143,19 +288,20 @@ impl Game {
                     } == y {
                        has_moved = false; 
                     }
+                    // the white pawns on the first rank in Horde can also move two steps
+                    if game.variant == Variant::Horde && color == PieceColor::White && y == 0 {
+                        has_moved = false;
+                    }
                     PieceType::Pawn
                 },
                 _ => {
-                    match c.to_string().parse::<i8>() {
-                        Ok(i) => {
-                            x += i;
-                            continue;
-                        },
-                        Err(_) => (),
+                    if let Ok(i) = c.to_string().parse::<i8>() {
+                        x += i;
+                        continue;
                     }
                 },
             }
-            game.live_pieces.insert(Square {x, y}, Piece { piece_type, color, pos: Square {x, y}, has_moved, ..template });
+            game.live_pieces.insert(Square {x, y}, Piece { piece_type, color, pos: Square {x, y}, has_moved });
             
             x += 1;
         }
@@ -171,60 +317,62 @@ impl Game {
         // 3 castling rights
         if fields.len() < 3 { return game; }
 
-        if fields[2].contains("Q") {
-            match game.live_pieces.get_mut(&Square::from((0, 0))) {
-                Some(rook) => rook.has_moved = false,
-                None => (),
-            }
-        }
-
-        if fields[2].contains("K") {
-            match game.live_pieces.get_mut(&Square::from((7, 0))) {
-                Some(rook) => rook.has_moved = false,
-                None => (),
-            }
-        }
+        for c in fields[2].chars() {
+            let (color, y) = if c.is_ascii_uppercase() { (PieceColor::White, 0) } else { (PieceColor::Black, 7) };
 
-        if fields[2].contains("q") {
-            match game.live_pieces.get_mut(&Square::from((0, 7))) {
-                Some(rook) => rook.has_moved = false,
-                None => (),
-            }
-        }
+            let king_x = match game.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == color && p.pos.y == y) {
+                Some(king) => king.pos.x,
+                None => continue,
+            };
+            let is_rook = |x: i8| matches!(game.live_pieces.get(&Square::at(x, y)), Some(p) if p.piece_type == PieceType::Rook && p.color == color);
+
+            // the outermost rook for K and Q, otherwise the rook on the file
+            let rook_x = match c.to_ascii_uppercase() {
+                'K' => (king_x + 1..8).rev().find(|x| is_rook(*x)),
+                'Q' => (0..king_x).find(|x| is_rook(*x)),
+                'A'..='H' => Some(c.to_ascii_uppercase() as i8 - 'A' as i8).filter(|x| is_rook(*x)),
+                _ => None,
+            };
 
-        if fields[2].contains("k") {
-            match game.live_pieces.get_mut(&Square::from((7, 7))) {
-                Some(rook) => rook.has_moved = false,
-                None => (),
+            if let Some(rook_x) = rook_x {
+                if let Some(rook) = game.live_pieces.get_mut(&Square::at(rook_x, y)) {
+                    rook.has_moved = false;
+                }
+                let file_letter = !"KQ".contains(c.to_ascii_uppercase());
+                if file_letter || king_x != 4 || (rook_x != 0 && rook_x != 7) {
+                    game.chess960 = true;
+                }
             }
         }
 
         // 4 possible en passant target
         if fields.len() < 4 { return game; }
 
-        if fields[3] != "-"
-        {
-            game.last_moved_to = Square::from(fields[3]);
-            game.last_moved_from = game.last_moved_to.moved(0, match game.turn {
-                PieceColor::White => 2,
-                PieceColor::Black => -2,
-            });
+        // the field is the square behind the pawn that moved two steps, game.turn is the side that moved it
+        if let Some(target) = Square::from_notation(fields[3]) {
+            let direction = match game.turn {
+                PieceColor::White => 1,
+                PieceColor::Black => -1,
+            };
+            let (from, to) = (target.moved(0, -direction), target.moved(0, direction));
+            if from.on_board() && to.on_board() {
+                game.last_moved_from = Some(from);
+                game.last_moved_to = Some(to);
+            }
         }
 
         // 5 halfmove clock
         if fields.len() < 5 { return game; }
 
-        match fields[4].parse() {
-            Ok(i) => game.fifty_move_rule = i,
-            Err(_) => (),
+        if let Ok(i) = fields[4].parse() {
+            game.fifty_move_rule = i;
         }
 
         // 6 fullmove number
         if fields.len() < 6 { return game; }
 
-        match fields[5].parse::<u32>() {
-            Ok(i) => game.fullmoves = i,
-            Err(_) => (),
+        if let Ok(i) = fields[5].parse::<u32>() {
+            game.fullmoves = i;
         }
 
         if game.turn == PieceColor::Black && game.fullmoves > 0 {
@@ -237,8 +385,35 @@ impl Game {
         
         game.post_move();
 
+        // post_move() counts a check in the position as a new one, the counters from the FEN are used instead
+        (game.white_checks, game.black_checks) = checks;
+        if let Some(result) = game.variant_result() {
+            game.result = result;
+            game.termination = Some(Termination::VariantRule);
+        }
+
         game
     }
+
+    // returns the FEN of the position the game started from
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    // returns the moves played since the start position, a move waiting for pawn_promotion() has no promotion yet
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    // returns the variant that is played
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    // returns why the game ended, None while it is ongoing
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
     
     // returns a FEN string of the current game
     pub fn to_fen(&self) -> String {
@@ -249,27 +424,24 @@ impl Game {
         while y >= 0 {
             let mut empty_spaces = 0;
             for x in 0..8 {
-                match self.live_pieces.get(&Square::from((x, y))) {
+                match self.live_pieces.get(&Square::at(x, y)) {
                     Some(piece) => {
                         if empty_spaces > 0 {
                             fen.push_str(empty_spaces.to_string().as_str());
                             empty_spaces = 0;
                         }
 
-                        let mut c = match piece.piece_type {
-                            PieceType::King => 'K',
-                            PieceType::Queen => 'Q',
-                            PieceType::Bishop => 'B',
-                            PieceType::Knight => 'N',
-                            PieceType::Rook => 'R',
-                            PieceType::Pawn => 'P',
-                        };
+                        let mut c = piece.piece_type.to_char();
 
                         if piece.color == PieceColor::Black {
                             c = c.to_ascii_lowercase();
                         }
 
                         fen.push(c);
+
+                        if self.variant == Variant::Crazyhouse && self.promoted & piece.pos.to_bitmap() != 0 {
+                            fen.push('~');
+                        }
                     },
                     None => empty_spaces += 1,
                 }
@@ -287,6 +459,20 @@ impl Game {
             y -= 1;
         }
 
+        // pockets
+        if self.variant == Variant::Crazyhouse {
+            fen.push('[');
+            for (pocket, white) in [(&self.white_pocket, true), (&self.black_pocket, false)] {
+                for piece_type in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn] {
+                    for _ in pocket.iter().filter(|p| **p == piece_type) {
+                        let c = piece_type.to_char();
+                        fen.push(if white { c } else { c.to_ascii_lowercase() });
+                    }
+                }
+            }
+            fen.push(']');
+        }
+
         // 2 turn
         fen.push_str(match self.turn {
             PieceColor::White => " w ",
@@ -294,87 +480,14 @@ impl Game {
         });
 
         // 3 castling rights
-        let mut any_castling_rights = false;
-
-        // white
-        match self.live_pieces.get(&Square::from((4, 0))) {
-            // white king
-            Some(king) => {
-                if king.piece_type == PieceType::King && king.has_moved == false {
-                    match self.live_pieces.get(&Square::from((7, 0))) {
-                        // white king rook
-                        Some(rook) => {
-                            if rook.piece_type == PieceType::Rook && rook.has_moved == false {
-                                any_castling_rights = true;
-                                fen.push('K');
-                            }
-                        },
-                        None => (),
-                    }
-
-                    match self.live_pieces.get(&Square::from((0, 0))) {
-                        // white queen rook
-                        Some(rook) => {
-                            if rook.piece_type == PieceType::Rook && rook.has_moved == false {
-                                any_castling_rights = true;
-                                fen.push('Q');
-                            }
-                        },
-                        None => (),
-                    }
-                }
-            },
-            None => (),
-        }
-
-        // black
-        match self.live_pieces.get(&Square::from((4, 7))) {
-            // black king
-            Some(king) => {
-                if king.piece_type == PieceType::King && king.has_moved == false {
-                    match self.live_pieces.get(&Square::from((7, 7))) {
-                        // black king rook
-                        Some(rook) => {
-                            if rook.piece_type == PieceType::Rook && rook.has_moved == false {
-                                any_castling_rights = true;
-                                fen.push('k');
-                            }
-                        },
-                        None => (),
-                    }
-
-                    match self.live_pieces.get(&Square::from((0, 7))) {
-                        // black queen rook
-                        Some(rook) => {
-                            if rook.piece_type == PieceType::Rook && rook.has_moved == false {
-                                any_castling_rights = true;
-                                fen.push('q');
-                            }
-                        },
-                        None => (),
-                    }
-                }
-            },
-            None => (),
-        }
-
-        if any_castling_rights {
-            fen.push(' ');
-        }
-        else {
-            fen.push_str("- ");
-        }
+        fen.push_str(&self.castling_field(false));
+        fen.push(' ');
 
         // 4 possible en passant targets
-        match self.live_pieces.get(&self.last_moved_to) {
-            Some(pawn) => {
-                if pawn.piece_type == PieceType::Pawn && self.last_moved_from.moved(0, pawn.get_direction() * 2) == self.last_moved_to {
-                    fen.push_str(self.last_moved_to.to_notation().to_ascii_lowercase().as_str());
-                    fen.push(' ');
-                }
-                else {
-                    fen.push_str("- ");
-                }
+        match self.en_passant_square() {
+            Some(square) => {
+                fen.push_str(square.to_notation().to_ascii_lowercase().as_str());
+                fen.push(' ');
             },
             None => fen.push_str("- "),
         }
@@ -386,24 +499,265 @@ impl Game {
         // 6 fullmove number
         fen.push_str(self.fullmoves.to_string().as_str());
 
+        // checks given in Three-check
+        if self.variant == Variant::ThreeCheck {
+            fen.push_str(&format!(" +{}+{}", self.white_checks, self.black_checks));
+        }
+
         fen
     }
 
+    // returns a FEN string where the castling rights are written with the files of the rooks (Shredder-FEN), used for Chess960
+    pub fn to_shredder_fen(&self) -> String {
+        let fen = self.to_fen();
+        let mut fields: Vec<&str> = fen.split(' ').collect();
+        let castling = self.castling_field(true);
+        fields[2] = &castling;
+        fields.join(" ")
+    }
+
+    // returns the castling field of FEN, with shredder the files of the rooks are always used, otherwise KQkq is
+    // used unless there is another rook further out on the same side (X-FEN)
+    fn castling_field(&self, shredder: bool) -> String {
+        let mut field = String::new();
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            let king = match self.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == color) {
+                Some(king) => king,
+                None => continue,
+            };
+
+            for short in [true, false] {
+                let rook_square = match self.castling_rook(king, short) {
+                    Some(square) => square,
+                    None => continue,
+                };
+
+                // another rook further out means the file has to be written
+                let outer_files = if short { rook_square.x + 1..8 } else { 0..rook_square.x };
+                let outermost = !outer_files.into_iter().any(|x| matches!(self.live_pieces.get(&Square::at(x, rook_square.y)), Some(p) if p.piece_type == PieceType::Rook && p.color == color));
+
+                let c = if shredder || !outermost {
+                    (b'A' + rook_square.x as u8) as char
+                } else if short {
+                    'K'
+                } else {
+                    'Q'
+                };
+
+                field.push(match color {
+                    PieceColor::White => c,
+                    PieceColor::Black => c.to_ascii_lowercase(),
+                });
+            }
+        }
+
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+
+    // returns a hash of the position that is the same for transpositions
+    // it takes pieces, turn, castling rights and en passant into account (but not the move counters)
+    pub fn hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for (square, piece) in &self.live_pieces {
+            let color_index = match piece.color {
+                PieceColor::White => 0,
+                PieceColor::Black => 1,
+            };
+            hash ^= zobrist::KEYS[(color_index * 6 + piece.piece_type.index()) * 64 + square.to_index() as usize];
+        }
+
+        let castling_rights = self.castling_rights();
+        for (i, right) in castling_rights.iter().enumerate() {
+            if *right {
+                hash ^= zobrist::KEYS[zobrist::CASTLING_OFFSET + i];
+            }
+        }
+
+        // en passant only matters if there is a pawn that can take
+        if let Some(pawn) = self.double_step_pawn() {
+            let can_take = [-1, 1].iter().any(|dx| match self.live_pieces.get(&pawn.pos.moved(*dx, 0)) {
+                Some(other) => other.piece_type == PieceType::Pawn && other.color != pawn.color,
+                None => false,
+            });
+            if can_take {
+                hash ^= zobrist::KEYS[zobrist::EN_PASSANT_OFFSET + pawn.pos.x as usize];
+            }
+        }
+
+        if self.turn == PieceColor::Black {
+            hash ^= zobrist::KEYS[zobrist::BLACK_TO_MOVE];
+        }
+
+        for (color_index, pocket) in [&self.white_pocket, &self.black_pocket].iter().enumerate() {
+            for (i, piece_type) in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn].iter().enumerate() {
+                let count = pocket.iter().filter(|p| *p == piece_type).count();
+                if count > 0 {
+                    hash ^= zobrist::KEYS[zobrist::POCKET_OFFSET + (color_index * 5 + i) * 16 + count.min(15)];
+                }
+            }
+        }
+
+        if self.variant == Variant::ThreeCheck {
+            hash ^= zobrist::KEYS[zobrist::CHECKS_OFFSET + self.white_checks.min(3) as usize];
+            hash ^= zobrist::KEYS[zobrist::CHECKS_OFFSET + 4 + self.black_checks.min(3) as usize];
+        }
+
+        hash
+    }
+
+    // returns the square behind a pawn that just moved two steps, it is there whether or not a pawn can take en passant
+    fn en_passant_square(&self) -> Option<Square> {
+        self.double_step_pawn().map(|pawn| pawn.pos.moved(0, -pawn.get_direction()))
+    }
+
+    // returns the pawn that just moved two steps, the only pawn that can be taken en passant
+    fn double_step_pawn(&self) -> Option<&Piece> {
+        let (from, to) = (self.last_moved_from?, self.last_moved_to?);
+        self.live_pieces.get(&to).filter(|pawn| pawn.piece_type == PieceType::Pawn && from.moved(0, pawn.get_direction() * 2) == to)
+    }
+
+    // returns the castling rights as [white short, white long, black short, black long]
+    // a right is kept as long as the king and the rook have not moved
+    fn castling_rights(&self) -> [bool; 4] {
+        let mut rights = [false; 4];
+
+        for (i, color) in [PieceColor::White, PieceColor::Black].iter().enumerate() {
+            if let Some(king) = self.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == *color) {
+                rights[i * 2] = self.castling_rook(king, true).is_some();
+                rights[i * 2 + 1] = self.castling_rook(king, false).is_some();
+            }
+        }
+
+        rights
+    }
+
+    // returns the square of the rook the king can castle with to the right (short) or to the left (long)
+    // only checks that neither has moved, in Chess960 it is the outermost unmoved rook on that side
+    fn castling_rook(&self, king: &Piece, short: bool) -> Option<Square> {
+        let back_rank = match king.color {
+            PieceColor::White => 0,
+            PieceColor::Black => 7,
+        };
+        if king.piece_type != PieceType::King || king.has_moved || king.pos.y != back_rank || !self.variant.has_castling() {
+            return None;
+        }
+
+        let is_castling_rook = |x: i8| matches!(self.live_pieces.get(&Square::at(x, back_rank)),
+            Some(rook) if rook.piece_type == PieceType::Rook && rook.color == king.color && !rook.has_moved);
+
+        let rook_x = if self.chess960 {
+            if short {
+                (king.pos.x + 1..8).rev().find(|x| is_castling_rook(*x))
+            } else {
+                (0..king.pos.x).find(|x| is_castling_rook(*x))
+            }
+        } else {
+            let x = if short { 7 } else { 0 };
+            Some(x).filter(|x| king.pos.x == 4 && is_castling_rook(*x))
+        };
+
+        rook_x.map(|x| Square::at(x, back_rank))
+    }
+
+    // returns the rook square if moving the king to "to" is castling
+    // in Chess960 castling is written as the king taking its own rook, otherwise the king moves two squares
+    fn castling_rook_for_move(&self, king: &Piece, to: Square) -> Option<Square> {
+        if self.chess960 {
+            let short = to.x > king.pos.x;
+            self.castling_rook(king, short).filter(|rook_square| *rook_square == to)
+        } else if to.y == king.pos.y && (to.x - king.pos.x).abs() == 2 {
+            self.castling_rook(king, to.x > king.pos.x)
+        } else {
+            None
+        }
+    }
+
+    // returns a bitmap of the legal castling moves of the king, see castling_rook_for_move() for how they are written
+    // the king and the rook always end on the same files as in normal chess, g and f when castling short, c and d when castling long
+    // every square between the king and the rook and their destinations must be empty and the king may not be in check or
+    // pass a square that is attacked
+    fn castling_moves(&self, king: &Piece) -> u64 {
+        let mut moves = 0;
+
+        let (own_color_bitmap, other_color_bitmap) = match king.color {
+            PieceColor::White => (self.white_bitmap, self.black_bitmap),
+            PieceColor::Black => (self.black_bitmap, self.white_bitmap),
+        };
+
+        for short in [true, false] {
+            let rook_square = match self.castling_rook(king, short) {
+                Some(square) => square,
+                None => continue,
+            };
+
+            let y = king.pos.y;
+            let (king_to_x, rook_to_x) = if short { (6, 5) } else { (2, 3) };
+
+            // the king and the rook do not block themselves
+            let own_color_bitmap = own_color_bitmap & !king.pos.to_bitmap() & !rook_square.to_bitmap();
+
+            let mut path = 0;
+            for x in king.pos.x.min(rook_square.x).min(king_to_x).min(rook_to_x)..=king.pos.x.max(rook_square.x).max(king_to_x).max(rook_to_x) {
+                path |= Square::at(x, y).to_bitmap();
+            }
+            if path & (own_color_bitmap | other_color_bitmap) != 0 {
+                continue;
+            }
+
+            let mut king_path = 0;
+            for x in king.pos.x.min(king_to_x)..=king.pos.x.max(king_to_x) {
+                king_path |= Square::at(x, y).to_bitmap();
+            }
+            if self.attacked_squares(!king.color, other_color_bitmap, own_color_bitmap) & king_path != 0 {
+                continue;
+            }
+
+            moves |= if self.chess960 { rook_square.to_bitmap() } else { Square::at(king_to_x, y).to_bitmap() };
+        }
+
+        moves
+    }
+
+    // returns a bitmap of all squares the pieces of the color attack, pawns only attack diagonally
+    fn attacked_squares(&self, color: PieceColor, own_color_bitmap: u64, other_color_bitmap: u64) -> u64 {
+        let mut attacked = 0;
+        for piece in self.live_pieces.values().filter(|p| p.color == color && p.pos.to_bitmap() & own_color_bitmap != 0) {
+            attacked |= if piece.piece_type == PieceType::Pawn {
+                piece.pos.moved(1, piece.get_direction()).to_bitmap() | piece.pos.moved(-1, piece.get_direction()).to_bitmap()
+            } else {
+                self.psuedo_legal_moves(piece, own_color_bitmap, other_color_bitmap)
+            };
+        }
+        attacked
+    }
+
     // removes any piece in the square and updates bitmaps
     fn capture(&mut self, square: &Square) {
 
         // remove piece and add it to captured lsit
-        match self.live_pieces.remove(&square) {
-            Some(piece) => {
+        if let Some(piece) = self.live_pieces.remove(square) {
+            match piece.color {
+                PieceColor::White => self.white_captured_pieces.push(piece.piece_type),
+                PieceColor::Black => self.black_captured_pieces.push(piece.piece_type),
+            }
+
+            // in Crazyhouse the piece goes to the pocket of the other side, promoted pieces turn back into pawns
+            if self.variant == Variant::Crazyhouse {
+                let piece_type = if self.promoted & square.to_bitmap() != 0 { PieceType::Pawn } else { piece.piece_type };
                 match piece.color {
-                    PieceColor::White => self.white_captured_pieces.push(piece.piece_type),
-                    PieceColor::Black => self.black_captured_pieces.push(piece.piece_type),
+                    PieceColor::White => self.black_pocket.push(piece_type),
+                    PieceColor::Black => self.white_pocket.push(piece_type),
                 }
+            }
+            self.promoted &= !square.to_bitmap();
 
-                // reset previous_states because it cant happen again after a capture
-                self.previous_states.clear();
-            },
-            None => (),
+            // reset previous_states because it cant happen again after a capture
+            self.previous_states.clear();
         }
         
         // update bitmaps
@@ -433,12 +787,12 @@ impl Game {
                 let moves_bitmap = self.legal_moves(piece);
                 for i in 0..64 {
                     if (moves_bitmap >> i) & 1 != 0 {
-                        moves.push(Square::from(i));
+                        moves.push(Square::at_index(i));
                     }
                 }
                 moves
             },
-            None => return Vec::new(),
+            None => Vec::new(),
         }
     }
 
@@ -450,14 +804,79 @@ impl Game {
         }
 
         match self.live_pieces.get(from) {
-            Some(piece) => {
-                if piece.color == self.turn {
-                    self.legal_moves(piece)
+            Some(piece) if piece.color == self.turn => self.legal_moves(piece),
+            _ => 0,
+        }
+    }
+
+    // returns all legal moves for the player whose turn it is
+    // the order is always the same: by from square index, then to square index, promotions as queen, rook, bishop, knight (king)
+    pub fn get_all_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        if self.result != ChessResult::Ongoing || self.promotion {
+            return moves;
+        }
+
+        for i in 0..64 {
+            let from = Square::at_index(i);
+            let piece = match self.live_pieces.get(&from) {
+                Some(piece) if piece.color == self.turn => piece,
+                _ => continue,
+            };
+
+            let moves_bitmap = self.legal_moves(piece);
+            for j in 0..64 {
+                if (moves_bitmap >> j) & 1 == 0 {
+                    continue;
+                }
+
+                let to = Square::at_index(j);
+                if piece.piece_type == PieceType::Pawn && (to.y == 0 || to.y == 7) {
+                    for class in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::King] {
+                        if self.can_promote_to(class) {
+                            moves.push(Move::with_promotion(from, to, class));
+                        }
+                    }
                 } else {
-                    0
+                    moves.push(Move::new(from, to));
+                }
+            }
+        }
+
+        // Crazyhouse drops
+        for piece_type in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn] {
+            let drops_bitmap = self.get_drops_bitmap(piece_type);
+            for i in 0..64 {
+                if (drops_bitmap >> i) & 1 != 0 {
+                    moves.push(Move::new_drop(piece_type, Square::at_index(i)));
                 }
+            }
+        }
+
+        moves
+    }
+
+    // does a move including the promotion and returns true if it was successful
+    // a move that leads to promotion must have move.promotion set, otherwise nothing is done
+    pub fn make_move(&mut self, m: &Move) -> bool {
+        if let Some(piece_type) = m.drop {
+            return self.do_drop(piece_type, &m.to);
+        }
+
+        let promotes = match self.live_pieces.get(&m.from) {
+            Some(piece) => piece.piece_type == PieceType::Pawn && (m.to.y == 0 || m.to.y == 7),
+            None => return false,
+        };
+
+        match (promotes, m.promotion) {
+            (true, Some(class)) => {
+                if !self.can_promote_to(class) {
+                    return false;
+                }
+                self.do_move(&m.from, &m.to) && self.pawn_promotion(class)
             },
-            None => 0,
+            (false, None) => self.do_move(&m.from, &m.to),
+            _ => false,
         }
     }
 
@@ -495,29 +914,119 @@ impl Game {
 
         if self.legal_moves(&piece) & to.to_bitmap() != 0 {
             // legal move
-            match self.force_move(&mut piece, *to) {
-                Ok(_) => true,
-                Err(_) => false,
+            let event = self.before_move_event(&Move::new(*from, *to));
+            let done = self.force_move(&mut piece, *to).is_ok();
+            if done {
+                self.history.push(Move::new(*from, *to));
+                self.after_move_event(event);
             }
+            done
         } else {
             // illegal move
             false
         }
     }
 
+    // returns a bitmap of the squares where the player whose turn it is can drop a piece of the type (Crazyhouse)
+    pub fn get_drops_bitmap(&self, piece_type: PieceType) -> u64 {
+        if self.result != ChessResult::Ongoing || self.promotion {
+            return 0;
+        }
+        self.drop_squares(piece_type)
+    }
+
+    // drops a piece from the pocket onto the board and returns true if it was successful (Crazyhouse)
+    pub fn do_drop(&mut self, piece_type: PieceType, to: &Square) -> bool {
+        if self.get_drops_bitmap(piece_type) & to.to_bitmap() == 0 {
+            return false;
+        }
+        let event = self.before_move_event(&Move::new_drop(piece_type, *to));
+
+        let pocket = match self.turn {
+            PieceColor::White => &mut self.white_pocket,
+            PieceColor::Black => &mut self.black_pocket,
+        };
+        if let Some(i) = pocket.iter().position(|p| *p == piece_type) {
+            pocket.remove(i);
+        }
+
+        // pawns dropped on their starting rank can move two steps
+        let start_rank = match self.turn {
+            PieceColor::White => 1,
+            PieceColor::Black => 6,
+        };
+        let has_moved = piece_type != PieceType::Pawn || to.y != start_rank;
+        self.live_pieces.insert(*to, Piece { piece_type, color: self.turn, pos: *to, has_moved });
+
+        match self.turn {
+            PieceColor::White => self.white_bitmap |= to.to_bitmap(),
+            PieceColor::Black => self.black_bitmap |= to.to_bitmap(),
+        }
+
+        self.fifty_move_rule += 1;
+        if piece_type == PieceType::Pawn {
+            self.fifty_move_rule = 0;
+        }
+        self.capture = false;
+        self.last_moved_from = Some(*to);
+        self.last_moved_to = Some(*to);
+        self.history.push(Move::new_drop(piece_type, *to));
+
+        self.post_move();
+        self.after_move_event(event);
+        true
+    }
+
+    // returns the drop squares for the player whose turn it is without checking if the game is over
+    // pieces go on empty squares, pawns not on the first or last rank and when in check the drop has to block it
+    fn drop_squares(&self, piece_type: PieceType) -> u64 {
+        let (pocket, own_color_bitmap, other_color_bitmap) = match self.turn {
+            PieceColor::White => (&self.white_pocket, self.white_bitmap, self.black_bitmap),
+            PieceColor::Black => (&self.black_pocket, self.black_bitmap, self.white_bitmap),
+        };
+        if self.variant != Variant::Crazyhouse || !pocket.contains(&piece_type) {
+            return 0;
+        }
+
+        let mut squares = !(own_color_bitmap | other_color_bitmap);
+        if piece_type == PieceType::Pawn {
+            squares &= !0xFF000000_000000FF;
+        }
+
+        if self.check {
+            let king_bitmap = match self.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == self.turn) {
+                Some(king) => king.pos.to_bitmap(),
+                None => return squares,
+            };
+            for i in 0..64 {
+                let square_bitmap = 1 << i;
+                if squares & square_bitmap != 0 && self.attacked_squares(!self.turn, other_color_bitmap, own_color_bitmap | square_bitmap) & king_bitmap != 0 {
+                    squares &= !square_bitmap;
+                }
+            }
+        }
+
+        squares
+    }
+
     // selects the piece to promote a pawn to. will return false if invalid PieceType whas passed
     pub fn pawn_promotion(&mut self, class: PieceType) -> bool {
-        // return false if class is king or pawn
-        if class == PieceType::King || class == PieceType::Pawn || !self.promotion {
+        // return false if class is king or pawn (a king is fine in Antichess)
+        if !self.can_promote_to(class) || !self.promotion {
             return false;
         }
 
         // do promotion and finish move with post_move()
-        match self.live_pieces.get_mut(&self.last_moved_to) {
+        match self.last_moved_to.and_then(|to| self.live_pieces.get_mut(&to)) {
             Some(piece) => {
                 piece.piece_type = class;
+                self.promoted |= piece.pos.to_bitmap();
                 self.promotion = false;
+                if let Some(m) = self.history.last_mut() {
+                    m.promotion = Some(class);
+                }
                 self.post_move();
+                self.after_promotion_event();
                 true
             },
             None => false,
@@ -528,6 +1037,8 @@ impl Game {
     pub fn declare_draw(&mut self) {
         if self.result == ChessResult::Ongoing {
             self.result = ChessResult::Draw;
+            self.termination = Some(Termination::Declared);
+            self.game_ended_event();
         }
     }
 
@@ -538,6 +1049,8 @@ impl Game {
                 PieceColor::White => ChessResult::WhiteWon,
                 PieceColor::Black => ChessResult::BlackWon,
             };
+            self.termination = Some(Termination::Declared);
+            self.game_ended_event();
         }
     }
 
@@ -549,13 +1062,17 @@ impl Game {
         } else if self.promotion {
             Err("Pawn has to be promoted first! call pawn_promotion()")
         } else {
-            let pos_bitmap = to.to_bitmap();
+            // in Chess960 castling moves the king onto its own rook, that is not a capture
+            let castle_rook = if piece.piece_type == PieceType::King { self.castling_rook_for_move(piece, to) } else { None };
+
+            let mut to = to;
+            let mut pos_bitmap = to.to_bitmap();
 
             // increment fifty_move_rule every move. Incremented before it might be reset.
             self.fifty_move_rule += 1;
             self.capture = false;
 
-            if (self.black_bitmap | self.white_bitmap) & pos_bitmap != 0 { // if there is other piece in pos, capture
+            if castle_rook.is_none() && (self.black_bitmap | self.white_bitmap) & pos_bitmap != 0 { // if there is other piece in pos, capture
 
                 self.capture(&to); // we capture it (we dont care what piece it was in this function)
                 self.fifty_move_rule = 0;
@@ -585,39 +1102,21 @@ impl Game {
                 }
             }
 
-            // castle
-            let (castle_bitmap_add, castle_bitmap_remove) = if piece.piece_type == PieceType::King && !piece.has_moved {
-                if piece.pos.moved(-2, 0) == to { // long castle
-                    let rook_pos = Square::from((0, piece.pos.y));
-                    let rook_to = to.moved(1, 0);
+            // castle, the rook is taken off the board until the king has moved because in Chess960 they can land on
+            // each others squares
+            let mut castling_rook = None;
+            let (castle_bitmap_add, castle_bitmap_remove) = match castle_rook {
+                Some(rook_square) => {
+                    let short = rook_square.x > piece.pos.x;
+                    to = Square::at(if short { 6 } else { 2 }, piece.pos.y);
+                    pos_bitmap = to.to_bitmap();
 
-                    if move_piece_hashmap(&mut self.live_pieces, &rook_pos, rook_to) {
-                        // update bitmap
-                        let castle_bitmap_remove = rook_pos.to_bitmap();
-                        let castle_bitmap_add = rook_to.to_bitmap();
+                    let rook_to = Square::at(if short { 5 } else { 3 }, piece.pos.y);
+                    castling_rook = self.live_pieces.remove(&rook_square).map(|rook| (rook, rook_to));
 
-                        (castle_bitmap_add, castle_bitmap_remove)
-                    } else {
-                        (0, 0)
-                    }
-                } else if piece.pos.moved(2, 0) == to { // short castle
-                    let rook_pos = Square::from((7, piece.pos.y));
-                    let rook_to = to.moved(-1, 0);
-
-                    if move_piece_hashmap(&mut self.live_pieces, &rook_pos, rook_to) {
-                        // update bitmap
-                        let castle_bitmap_remove = rook_pos.to_bitmap();
-                        let castle_bitmap_add = rook_to.to_bitmap();
-
-                        (castle_bitmap_add, castle_bitmap_remove)
-                    } else {
-                        (0, 0)
-                    }
-                } else {
-                    (0, 0)
-                }
-            } else {
-                (0, 0)
+                    (rook_to.to_bitmap(), rook_square.to_bitmap())
+                },
+                None => (0, 0),
             };
 
             // update bitmap
@@ -633,11 +1132,26 @@ impl Game {
             }
 
             // we set the last_moved data
-            self.last_moved_from = piece.pos;
-            self.last_moved_to = to;
+            self.last_moved_from = Some(piece.pos);
+            self.last_moved_to = Some(to);
+
+            // promoted pieces stay promoted
+            if self.promoted & piece.pos.to_bitmap() != 0 {
+                self.promoted = (self.promoted & !piece.pos.to_bitmap()) | pos_bitmap;
+            }
 
             // move the piece in the hashmap and set has_moved and pos
             move_piece_hashmap(&mut self.live_pieces, &piece.pos, to);
+            if let Some((mut rook, rook_to)) = castling_rook {
+                rook.has_moved = true;
+                rook.pos = rook_to;
+                self.live_pieces.insert(rook_to, rook);
+            }
+
+            // in Atomic every capture is an explosion
+            if self.capture && self.variant == Variant::Atomic {
+                self.explode(to);
+            }
             
             // check for check, game over, 50 move rule, draw by repetition or insufficient material, and changes turn
             if !self.promotion {
@@ -672,10 +1186,18 @@ impl Game {
                 break;
             }
         }
+        self.check = self.variant_check(self.check);
+        if self.check {
+            match self.turn {
+                PieceColor::White => self.white_checks += 1,
+                PieceColor::Black => self.black_checks += 1,
+            }
+        }
 
         // 50 move rule, check mate will take precedence
         if self.fifty_move_rule >= 100 {
             self.result = ChessResult::Draw;
+            self.termination = Some(Termination::FiftyMoves);
         }
 
         // draw by repetition rule, check mate will take precedence
@@ -685,25 +1207,16 @@ impl Game {
                 *val += 1;
                 if *val >= 3 {
                     self.result = ChessResult::Draw;
+                    self.termination = Some(Termination::Repetition);
                 }
             },
             None => _ = self.previous_states.insert(board_value, 1),
         }
 
         // draw by insufficient material
-        if self.live_pieces.len() <= 3 {
-            let mut do_draw = true;
-            for (_, piece) in &self.live_pieces {
-                match piece.piece_type {
-                    PieceType::King => continue,
-                    PieceType::Bishop => continue,
-                    PieceType::Knight => continue,
-                    _ => { do_draw = false; break; },
-                }
-            }
-            if do_draw {
-                self.result = ChessResult::Draw;
-            }
+        if self.insufficient_material() {
+            self.result = ChessResult::Draw;
+            self.termination = Some(Termination::InsufficientMaterial);
         }
 
         // increment fullmoves
@@ -725,7 +1238,12 @@ impl Game {
                 break;
             }
         }
-        if has_legal_moves == false {
+        // a drop can also save the king in Crazyhouse
+        if !has_legal_moves {
+            has_legal_moves = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn]
+                .iter().any(|piece_type| self.drop_squares(*piece_type) != 0);
+        }
+        if !has_legal_moves {
             // the game is over!
             // change result
             self.result = if self.check {
@@ -738,6 +1256,13 @@ impl Game {
                 // stale mate
                 ChessResult::Draw
             };
+            self.termination = Some(if self.check { Termination::Checkmate } else { Termination::Stalemate });
+        }
+
+        // the variant can end the game in other ways
+        if let Some(result) = self.variant_result() {
+            self.result = result;
+            self.termination = Some(Termination::VariantRule);
         }
     }
 
@@ -751,32 +1276,9 @@ impl Game {
         let pos_bitmap = piece.pos.to_bitmap();
         let mut moves = self.psuedo_legal_moves(piece, own_color_bitmap, other_color_bitmap);
 
-        // add castling to moves if legal
-        if piece.piece_type == PieceType::King && !piece.has_moved && !self.check { // cant escape check by castling
-
-            // castle to the left (long castle)
-            match self.live_pieces.get(&Square {x: 0, y: piece.pos.y}) {
-                Some(rook) => {
-                    if rook.piece_type == PieceType::Rook &&
-                        !rook.has_moved &&
-                        bitmap_line(piece.pos, -1, 0, own_color_bitmap, other_color_bitmap) & Square::from((1, piece.pos.y)).to_bitmap() != 0 {
-                        moves |= piece.pos.moved(-2, 0).to_bitmap();
-                    }
-                },
-                None => {},
-            }
-
-            // castle to the right (short castle)
-            match self.live_pieces.get(&Square {x: 7, y: piece.pos.y}) {
-                Some(rook) => {
-                    if rook.piece_type == PieceType::Rook &&
-                        !rook.has_moved &&
-                        bitmap_line(piece.pos, 1, 0, own_color_bitmap, other_color_bitmap) & Square::from((6, piece.pos.y)).to_bitmap() != 0 {
-                        moves |= piece.pos.moved(2, 0).to_bitmap();
-                    }
-                },
-                None => {},
-            }
+        // there is no check in Antichess, the king is like any other piece
+        if self.variant == Variant::Antichess {
+            return self.variant_moves(piece, moves);
         }
 
         // remove everything that puts the king in check
@@ -791,34 +1293,41 @@ impl Game {
         // get position of king (assumes the king wasnt moved)
         let own_king_bitmap = match self.live_pieces.iter().find(|(_, x)| x.piece_type == PieceType::King && x.color == piece.color) {
             Some((pos, _)) => pos.to_bitmap(),
-            None => return moves, // if there is no king we say that all psuedo legal moves are legal
+            None => return self.variant_moves(piece, moves), // if there is no king we say that all psuedo legal moves are legal
         };
 
+        // explosions change which moves are legal too much for the loop below
+        if self.variant == Variant::Atomic {
+            moves = self.atomic_legal_moves(piece, moves, own_king_bitmap);
+            if piece.piece_type == PieceType::King {
+                moves |= self.castling_moves(piece);
+            }
+            return self.variant_moves(piece, moves);
+        }
+
         // loops through all moves in moves
         for i in 0..64 {
             if (moves >> i) & 1 == 0 {
                 continue;
             }
             
-            let possible_move = Square::from(i);
+            let possible_move = Square::at_index(i);
             let possible_move_bitmap = possible_move.to_bitmap();
             
             // remove old position and add new position to bitmap:
             let new_own_color_bitmap = (own_color_bitmap & !pos_bitmap) | possible_move_bitmap;
 
             // only changes if we take and only matters for en passant
-            let new_other_color_bitmap;
-
             // scary situation here: en passant may be one of the allowed moves 
             // and we cant make the assumption that we can take a piece that was pinned because we will not occupy its square
             // we know it is en passant if the piece is a pawn and the psuedo legal move is diagonal and to an empty space
-            if piece.piece_type == PieceType::Pawn && piece.pos.x != possible_move.x && other_color_bitmap & possible_move_bitmap == 0 {
+            let new_other_color_bitmap = if piece.piece_type == PieceType::Pawn && piece.pos.x != possible_move.x && other_color_bitmap & possible_move_bitmap == 0 {
                 // en passant!!!
-                new_other_color_bitmap = other_color_bitmap & !possible_move.moved(0, -piece.get_direction()).to_bitmap();
+                other_color_bitmap & !possible_move.moved(0, -piece.get_direction()).to_bitmap()
             } else {
                 // not en passant, just remove the square the move is to from opponent bitmap
-                new_other_color_bitmap = other_color_bitmap & !possible_move_bitmap;
-            }
+                other_color_bitmap & !possible_move_bitmap
+            };
 
             // get king position (even if it was moved)
             let own_king_bitmap = if piece.piece_type == PieceType::King {
@@ -837,31 +1346,23 @@ impl Game {
                 }
             }
         }
-        // remove castle if the in between square was in check
+        // castling is checked on its own since the king passes several squares
         if piece.piece_type == PieceType::King {
-            // left castle (long)
-            if piece.pos.moved(-1, 0).to_bitmap() & moves == 0 {
-                moves &= !piece.pos.moved(-2, 0).to_bitmap();
-            }
-
-            // right castle (short)
-            if piece.pos.moved(1, 0).to_bitmap() & moves == 0 {
-                moves &= !piece.pos.moved(2, 0).to_bitmap();
-            }
+            moves |= self.castling_moves(piece);
         }
 
-        moves
+        self.variant_moves(piece, moves)
     }
 
     // returns a bitmap of all possible moves for that piece without considering check, and does not include castling
     fn psuedo_legal_moves(&self, piece : &Piece, own_color_bitmap : u64, other_color_bitmap : u64) -> u64 {
         match piece.piece_type {
-            PieceType::King => return self.psuedo_legal_moves_king(piece, own_color_bitmap),
-            PieceType::Queen => return self.psuedo_legal_moves_queen(piece, own_color_bitmap, other_color_bitmap),
-            PieceType::Bishop => return self.psuedo_legal_moves_bishop(piece, own_color_bitmap, other_color_bitmap),
-            PieceType::Knight => return self.psuedo_legal_moves_knight(piece, own_color_bitmap),
-            PieceType::Rook => return self.psuedo_legal_moves_rook(piece, own_color_bitmap, other_color_bitmap),
-            PieceType::Pawn => return self.psuedo_legal_moves_pawn(piece, own_color_bitmap, other_color_bitmap),
+            PieceType::King => self.psuedo_legal_moves_king(piece, own_color_bitmap),
+            PieceType::Queen => self.psuedo_legal_moves_queen(piece, own_color_bitmap, other_color_bitmap),
+            PieceType::Bishop => self.psuedo_legal_moves_bishop(piece, own_color_bitmap, other_color_bitmap),
+            PieceType::Knight => self.psuedo_legal_moves_knight(piece, own_color_bitmap),
+            PieceType::Rook => self.psuedo_legal_moves_rook(piece, own_color_bitmap, other_color_bitmap),
+            PieceType::Pawn => self.psuedo_legal_moves_pawn(piece, own_color_bitmap, other_color_bitmap),
         }
     }
     
@@ -941,23 +1442,130 @@ impl Game {
 
         // check for en passant
         // we can assume that it is of the opponents color because last_moved_to is always the opponent
-        match self.live_pieces.get(&self.last_moved_to) {
-            Some(last_moved_piece) => {
-                // conditions for en passant: last moved piece was a pawn and it moved 2 steps and it is next to our pawn
-                if last_moved_piece.piece_type == PieceType::Pawn && // was pawn
-                    self.last_moved_to.y + direction * 2 == self.last_moved_from.y && // moved 2 steps
-                    (piece.pos.moved(-1, 0).to_bitmap() | piece.pos.moved(1, 0).to_bitmap()) & last_moved_piece.pos.to_bitmap() != 0 { // is next to out pawn 
-                    moves |= self.last_moved_to.moved(0, direction).to_bitmap(); 
-                }
-            },
-            None => {}, // likely the last moved data wasnt set (as in the start)
-        };
+        if let Some(last_moved_piece) = self.double_step_pawn() {
+            // conditions for en passant: last moved piece was a pawn that moved 2 steps and it is next to our pawn
+            if (piece.pos.moved(-1, 0).to_bitmap() | piece.pos.moved(1, 0).to_bitmap()) & last_moved_piece.pos.to_bitmap() != 0 { // is next to out pawn 
+                moves |= last_moved_piece.pos.moved(0, direction).to_bitmap(); 
+            }
+        }
 
         moves 
     }
     
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the whole state is too much to read, the FEN tells the position
+impl fmt::Debug for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Game")
+            .field("fen", &self.to_fen())
+            .field("variant", &self.variant)
+            .field("result", &self.result)
+            .field("promotion", &self.promotion)
+            .finish()
+    }
+}
+
+// the board with white at the bottom, uppercase letters for white and lowercase for black
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in (0..8).rev() {
+            write!(f, "{}", y + 1)?;
+            for x in 0..8 {
+                match self.live_pieces.get(&Square::at(x, y)) {
+                    Some(piece) => write!(f, " {}", piece)?,
+                    None => write!(f, " .")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        write!(f, "  a b c d e f g h")
+    }
+}
+
+// reads FEN of a standard game, unlike from_fen() it returns an error if the FEN can not be read
+// use Game::from_str_variant() for the pockets of Crazyhouse and the checks of Three-check
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Game::from_str_variant(fen, Variant::Standard)
+    }
+}
+
+impl Game {
+    // reads FEN of a variant, unlike from_fen_variant() it returns an error if the FEN can not be read
+    pub fn from_str_variant(fen: &str, variant: Variant) -> Result<Self, String> {
+        check_fen(fen, variant)?;
+        Ok(Game::from_fen_variant(fen, variant))
+    }
+}
+
+// returns an error if the FEN is not written correctly, it does not check if the position is legal (see builder)
+// the placement and the turn are needed, the other fields can be left out
+fn check_fen(fen: &str, variant: Variant) -> Result<(), String> {
+    let has_checks = fen.split_whitespace().any(|field| field.contains('+'));
+    if has_checks && variant != Variant::ThreeCheck {
+        return Err(format!("check counters are only read in Three-check, not in {}", variant.name()));
+    }
+    let fields: Vec<&str> = fen.split_whitespace().filter(|field| !field.contains('+')).collect();
+    if fields.len() < 2 || fields.len() > 6 {
+        return Err(format!("expected 2 to 6 fields in \"{}\"", fen));
+    }
+
+    let placement = fields[0].split('[').next().unwrap_or("");
+    let ranks: Vec<&str> = placement.split('/').collect();
+    // Crazyhouse pockets can be written as a ninth rank
+    if ranks.len() != 8 && ranks.len() != 9 {
+        return Err(format!("expected 8 ranks in \"{}\"", placement));
+    }
+    let has_pockets = fields[0].contains('[') || ranks.len() == 9 || fields[0].contains('~');
+    if has_pockets && variant != Variant::Crazyhouse {
+        return Err(format!("pockets and promoted pieces are only read in Crazyhouse, not in {}", variant.name()));
+    }
+    for rank in &ranks[..8] {
+        let mut width = 0;
+        for c in rank.chars() {
+            match c {
+                '1'..='8' => width += c as u32 - '0' as u32,
+                '~' => (),
+                c if PieceType::from_char(c).is_some() => width += 1,
+                c => return Err(format!("unknown piece '{}'", c)),
+            }
+        }
+        if width != 8 {
+            return Err(format!("the rank \"{}\" is not 8 squares wide", rank));
+        }
+    }
+
+    if fields[1] != "w" && fields[1] != "b" {
+        return Err(format!("the turn is \"{}\" and not w or b", fields[1]));
+    }
+    if let Some(castling) = fields.get(2) {
+        if *castling != "-" && !castling.chars().all(|c| "KQkqABCDEFGHabcdefgh".contains(c)) {
+            return Err(format!("invalid castling rights \"{}\"", castling));
+        }
+    }
+    if let Some(en_passant) = fields.get(3) {
+        if *en_passant != "-" && !matches!(en_passant.parse::<Square>(), Ok(square) if square.y == 2 || square.y == 5) {
+            return Err(format!("invalid en passant square \"{}\"", en_passant));
+        }
+    }
+    for counter in fields.iter().skip(4) {
+        if counter.parse::<u32>().is_err() {
+            return Err(format!("\"{}\" is not a move counter", counter));
+        }
+    }
+
+    Ok(())
+}
+
 // changes the hashmap key, sets the pieces new pos and the has_moved flag, returns false if failed or key not in hashmap or new key already in hashmap
 fn move_piece_hashmap(hash_map: &mut HashMap<Square, Piece>, from: &Square, to: Square) -> bool {
     let mut piece = match hash_map.remove(from) {
@@ -977,7 +1585,8 @@ fn move_piece_hashmap(hash_map: &mut HashMap<Square, Piece>, from: &Square, to:
     true
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub piece_type: PieceType,
     pub color: PieceColor,
@@ -985,6 +1594,17 @@ pub struct Piece {
     pub has_moved: bool, // used for castling
 }
 
+// the letter used in FEN, uppercase for white
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = self.piece_type.to_char();
+        match self.color {
+            PieceColor::White => write!(f, "{}", c),
+            PieceColor::Black => write!(f, "{}", c.to_ascii_lowercase()),
+        }
+    }
+}
+
 impl Piece {
     fn get_direction(&self) -> i8 {
         match self.color {
@@ -994,69 +1614,116 @@ impl Piece {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+// a square on the board, the constructors return None for squares outside of it
+// the move generation steps outside of the board with Square::at() and moved(), those squares stay in the crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Square {
-    pub x: i8, // A-H (represented in code as 0-7)
-    pub y: i8, // 1-8 (represented in code as 0-7)
+    x: i8, // A-H (represented in code as 0-7)
+    y: i8, // 1-8 (represented in code as 0-7)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum File {
+    A, B, C, D, E, F, G, H,
 }
 
-impl From<i8> for Square {
-    fn from(value: i8) -> Self {
-        Self {x: value % 8, y: value / 8}
+impl File {
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    // 0 is the a-file
+    pub fn from_index(index: i8) -> Option<Self> {
+        File::ALL.get(usize::try_from(index).ok()?).copied()
+    }
+
+    pub fn to_index(self) -> i8 {
+        self as i8
     }
+
+    // lowercase as in "e4"
+    pub fn to_char(self) -> char {
+        (b'a' + self as u8) as char
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rank {
+    First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth,
 }
 
-impl From<(i8, i8)> for Square {
-    // initialize from a tuple of (i8, i8)
-    fn from(pos: (i8, i8)) -> Self {
-        Self {x: pos.0, y: pos.1}
+impl Rank {
+    pub const ALL: [Rank; 8] = [Rank::First, Rank::Second, Rank::Third, Rank::Fourth, Rank::Fifth, Rank::Sixth, Rank::Seventh, Rank::Eighth];
+
+    // 0 is the first rank
+    pub fn from_index(index: i8) -> Option<Self> {
+        Rank::ALL.get(usize::try_from(index).ok()?).copied()
+    }
+
+    pub fn to_index(self) -> i8 {
+        self as i8
+    }
+
+    pub fn to_char(self) -> char {
+        (b'1' + self as u8) as char
     }
 }
 
-impl From<&str> for Square {
-    // initialize from letter-number notation
-    fn from(pos: &str) -> Self {
-        let mut pos = pos.trim().chars();
-        let x: i8 = match pos.next(){
-            Some(c) => {
-                match c.to_ascii_uppercase() {
-                    'A' => 0,
-                    'B' => 1,
-                    'C' => 2,
-                    'D' => 3,
-                    'E' => 4,
-                    'F' => 5,
-                    'G' => 6,
-                    'H' => 7,
-                    _ => -1,
-                }
-            },
-            None => -1,
-        };
+// lowercase algebraic notation such as "e4"
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_notation().to_ascii_lowercase())
+    }
+}
 
-        let y: i8 = match pos.next(){
-            Some(c) => {
-                match c {
-                    '1' => 0,
-                    '2' => 1,
-                    '3' => 2,
-                    '4' => 3,
-                    '5' => 4,
-                    '6' => 5,
-                    '7' => 6,
-                    '8' => 7,
-                    _ => -1,
-                }
-            },
-            None => -1,
-        };
-        Self {x: x, y: y}
+// reads "e4" or "E4", see Square::from_notation()
+impl FromStr for Square {
+    type Err = String;
+
+    fn from_str(notation: &str) -> Result<Self, Self::Err> {
+        Square::from_notation(notation).ok_or(format!("\"{}\" is not a square", notation))
     }
 }
 
 impl Square {
-    pub fn from_u8(pos: (u8, u8)) -> Self {
-        Self { x: pos.0 as i8, y: pos.1 as i8 }
+    pub const fn new(file: File, rank: Rank) -> Self {
+        Self { x: file as i8, y: rank as i8 }
+    }
+
+    // creates a square from an index that starts at 0 for A1, 1 for B1 and ends at 63 for H8
+    pub fn from_index(index: i8) -> Option<Self> {
+        if !(0..64).contains(&index) {
+            return None;
+        }
+        Some(Self::at_index(index))
+    }
+
+    // creates a square from (x, y) where both are 0-7
+    pub fn from_tuple(pos: (i8, i8)) -> Option<Self> {
+        Some(Self::new(File::from_index(pos.0)?, Rank::from_index(pos.1)?))
+    }
+
+    // creates a square from chess coordinates such as "A1", "b1" or "H8"
+    pub fn from_notation(notation: &str) -> Option<Self> {
+        let mut chars = notation.trim().chars();
+        let (file, rank) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => (file.to_ascii_lowercase(), rank),
+            _ => return None,
+        };
+        let file = File::ALL.into_iter().find(|f| f.to_char() == file)?;
+        let rank = Rank::ALL.into_iter().find(|r| r.to_char() == rank)?;
+        Some(Self::new(file, rank))
+    }
+
+    // all 64 squares in the order of to_index(), A1, B1 and so on until H8
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Self::at_index)
+    }
+
+    pub fn file(&self) -> File {
+        File::ALL[self.x as usize]
+    }
+
+    pub fn rank(&self) -> Rank {
+        Rank::ALL[self.y as usize]
     }
 
     // returns the square number as if they were indexed from 0,0 to 7,0 to 1,0 and so on until 7,7
@@ -1068,17 +1735,53 @@ impl Square {
         format!("{}{}", (b'A' + self.x as u8) as char, self.y + 1)
     }
 
+    pub fn to_tuple(&self) -> (i8, i8) {
+        (self.x, self.y)
+    }
+
+    // A1 is dark and H1 light
+    pub fn is_light(&self) -> bool {
+        (self.x + self.y) % 2 == 1
+    }
+
+    // the number of moves a king needs to go from one square to the other
+    pub fn distance(&self, other: &Square) -> i8 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    pub fn same_file(&self, other: &Square) -> bool {
+        self.x == other.x
+    }
+
+    pub fn same_rank(&self, other: &Square) -> bool {
+        self.y == other.y
+    }
+
+    // true if a bishop could move between the squares on an empty board, or they are the same square
+    pub fn same_diagonal(&self, other: &Square) -> bool {
+        (self.x - other.x).abs() == (self.y - other.y).abs()
+    }
+
+    // creates a square without checking it, for the move generation that steps outside the board and checks after
+    pub(crate) const fn at(x: i8, y: i8) -> Self {
+        Self { x, y }
+    }
+
+    pub(crate) const fn at_index(index: i8) -> Self {
+        Self { x: index % 8, y: index / 8 }
+    }
+
+    pub(crate) fn on_board(&self) -> bool {
+        (0..8).contains(&self.x) && (0..8).contains(&self.y)
+    }
+
     // returns the position as a bitmap, if the position is outside the board it returns 0 (empty bitmap)
-    fn to_bitmap(&self) -> u64 {
-        if self.x < 0 || self.x >= 8 || self.y < 0 || self.y >= 8 {
+    fn to_bitmap(self) -> u64 {
+        if !self.on_board() {
             return 0;
         }
 
-        (1 << self.y*8) << self.x
-    }
-
-    pub fn to_tuple(&self) -> (i8, i8) {
-        (self.x, self.y)
+        (1 << (self.y*8)) << self.x
     }
 
     // returns a new square positioned dx, dy relative, can be outside of board!
@@ -1088,7 +1791,159 @@ impl Square {
 
 }
 
-#[derive(Clone, Copy, PartialEq)]
+// named squares such as Square::E4
+impl Square {
+    pub const A1: Square = Square { x: 0, y: 0 };
+    pub const B1: Square = Square { x: 1, y: 0 };
+    pub const C1: Square = Square { x: 2, y: 0 };
+    pub const D1: Square = Square { x: 3, y: 0 };
+    pub const E1: Square = Square { x: 4, y: 0 };
+    pub const F1: Square = Square { x: 5, y: 0 };
+    pub const G1: Square = Square { x: 6, y: 0 };
+    pub const H1: Square = Square { x: 7, y: 0 };
+    pub const A2: Square = Square { x: 0, y: 1 };
+    pub const B2: Square = Square { x: 1, y: 1 };
+    pub const C2: Square = Square { x: 2, y: 1 };
+    pub const D2: Square = Square { x: 3, y: 1 };
+    pub const E2: Square = Square { x: 4, y: 1 };
+    pub const F2: Square = Square { x: 5, y: 1 };
+    pub const G2: Square = Square { x: 6, y: 1 };
+    pub const H2: Square = Square { x: 7, y: 1 };
+    pub const A3: Square = Square { x: 0, y: 2 };
+    pub const B3: Square = Square { x: 1, y: 2 };
+    pub const C3: Square = Square { x: 2, y: 2 };
+    pub const D3: Square = Square { x: 3, y: 2 };
+    pub const E3: Square = Square { x: 4, y: 2 };
+    pub const F3: Square = Square { x: 5, y: 2 };
+    pub const G3: Square = Square { x: 6, y: 2 };
+    pub const H3: Square = Square { x: 7, y: 2 };
+    pub const A4: Square = Square { x: 0, y: 3 };
+    pub const B4: Square = Square { x: 1, y: 3 };
+    pub const C4: Square = Square { x: 2, y: 3 };
+    pub const D4: Square = Square { x: 3, y: 3 };
+    pub const E4: Square = Square { x: 4, y: 3 };
+    pub const F4: Square = Square { x: 5, y: 3 };
+    pub const G4: Square = Square { x: 6, y: 3 };
+    pub const H4: Square = Square { x: 7, y: 3 };
+    pub const A5: Square = Square { x: 0, y: 4 };
+    pub const B5: Square = Square { x: 1, y: 4 };
+    pub const C5: Square = Square { x: 2, y: 4 };
+    pub const D5: Square = Square { x: 3, y: 4 };
+    pub const E5: Square = Square { x: 4, y: 4 };
+    pub const F5: Square = Square { x: 5, y: 4 };
+    pub const G5: Square = Square { x: 6, y: 4 };
+    pub const H5: Square = Square { x: 7, y: 4 };
+    pub const A6: Square = Square { x: 0, y: 5 };
+    pub const B6: Square = Square { x: 1, y: 5 };
+    pub const C6: Square = Square { x: 2, y: 5 };
+    pub const D6: Square = Square { x: 3, y: 5 };
+    pub const E6: Square = Square { x: 4, y: 5 };
+    pub const F6: Square = Square { x: 5, y: 5 };
+    pub const G6: Square = Square { x: 6, y: 5 };
+    pub const H6: Square = Square { x: 7, y: 5 };
+    pub const A7: Square = Square { x: 0, y: 6 };
+    pub const B7: Square = Square { x: 1, y: 6 };
+    pub const C7: Square = Square { x: 2, y: 6 };
+    pub const D7: Square = Square { x: 3, y: 6 };
+    pub const E7: Square = Square { x: 4, y: 6 };
+    pub const F7: Square = Square { x: 5, y: 6 };
+    pub const G7: Square = Square { x: 6, y: 6 };
+    pub const H7: Square = Square { x: 7, y: 6 };
+    pub const A8: Square = Square { x: 0, y: 7 };
+    pub const B8: Square = Square { x: 1, y: 7 };
+    pub const C8: Square = Square { x: 2, y: 7 };
+    pub const D8: Square = Square { x: 3, y: 7 };
+    pub const E8: Square = Square { x: 4, y: 7 };
+    pub const F8: Square = Square { x: 5, y: 7 };
+    pub const G8: Square = Square { x: 6, y: 7 };
+    pub const H8: Square = Square { x: 7, y: 7 };
+}
+
+// a move from one square to another, promotion is set when a pawn reaches the last rank
+// in Crazyhouse a move can also be a drop of a piece from the pocket, then from is the same as to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceType>,
+    pub drop: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self { from, to, promotion: None, drop: None }
+    }
+
+    pub fn with_promotion(from: Square, to: Square, promotion: PieceType) -> Self {
+        Self { from, to, promotion: Some(promotion), drop: None }
+    }
+
+    pub fn new_drop(piece_type: PieceType, to: Square) -> Self {
+        Self { from: to, to, promotion: None, drop: Some(piece_type) }
+    }
+
+    // creates a move from UCI notation such as "e2e4", "e7e8q" or "N@f3" for drops, returns None if it is not valid notation
+    pub fn from_uci(uci: &str) -> Option<Self> {
+        let uci = uci.trim();
+        if uci.len() < 4 || uci.len() > 5 || !uci.is_ascii() {
+            return None;
+        }
+
+        if &uci[1..2] == "@" {
+            let to = Square::from_notation(&uci[2..4])?;
+            return match PieceType::from_char(uci.chars().next()?) {
+                Some(piece_type) if piece_type != PieceType::King && uci.len() == 4 => Some(Self::new_drop(piece_type, to)),
+                _ => None,
+            };
+        }
+
+        let from = Square::from_notation(&uci[0..2])?;
+        let to = Square::from_notation(&uci[2..4])?;
+
+        // promoting to a king is only legal in Antichess but it can still be written
+        let promotion = match uci[4..].chars().next() {
+            None => None,
+            Some(c) => match PieceType::from_char(c) {
+                Some(PieceType::Pawn) | None => return None,
+                class => class,
+            },
+        };
+
+        Some(Self { from, to, promotion, drop: None })
+    }
+
+    // returns the move in UCI notation such as "e2e4", "e7e8q" or "N@f3"
+    pub fn to_uci(&self) -> String {
+        if let Some(piece_type) = self.drop {
+            return format!("{}@{}", piece_type.to_char(), self.to.to_notation().to_ascii_lowercase());
+        }
+
+        let mut uci = format!("{}{}", self.from.to_notation(), self.to.to_notation()).to_ascii_lowercase();
+        if let Some(class) = self.promotion {
+            uci.push(class.to_char().to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
+// UCI notation
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+// reads UCI notation, see Move::from_uci()
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(uci: &str) -> Result<Self, Self::Err> {
+        Move::from_uci(uci).ok_or(format!("\"{}\" is not a move in UCI notation", uci))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     King,
     Queen,
@@ -1098,7 +1953,69 @@ pub enum PieceType {
     Pawn,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+impl PieceType {
+    // returns the letter used in FEN and SAN, uppercase
+    fn to_char(self) -> char {
+        match self {
+            PieceType::King => 'K',
+            PieceType::Queen => 'Q',
+            PieceType::Bishop => 'B',
+            PieceType::Knight => 'N',
+            PieceType::Rook => 'R',
+            PieceType::Pawn => 'P',
+        }
+    }
+
+    // returns the type of a letter in either case
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'K' => Some(PieceType::King),
+            'Q' => Some(PieceType::Queen),
+            'B' => Some(PieceType::Bishop),
+            'N' => Some(PieceType::Knight),
+            'R' => Some(PieceType::Rook),
+            'P' => Some(PieceType::Pawn),
+            _ => None,
+        }
+    }
+
+    // returns 0-5 in the order the types are declared, used to index tables
+    fn index(&self) -> usize {
+        match self {
+            PieceType::King => 0,
+            PieceType::Queen => 1,
+            PieceType::Bishop => 2,
+            PieceType::Knight => 3,
+            PieceType::Rook => 4,
+            PieceType::Pawn => 5,
+        }
+    }
+}
+
+// reads a letter in either case ("N", "n") or a name ("knight")
+impl FromStr for PieceType {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return PieceType::from_char(c).ok_or(format!("\"{}\" is not a piece", text));
+        }
+        match text.to_ascii_lowercase().as_str() {
+            "king" => Ok(PieceType::King),
+            "queen" => Ok(PieceType::Queen),
+            "bishop" => Ok(PieceType::Bishop),
+            "knight" => Ok(PieceType::Knight),
+            "rook" => Ok(PieceType::Rook),
+            "pawn" => Ok(PieceType::Pawn),
+            _ => Err(format!("\"{}\" is not a piece", text)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceColor {
     White,
     Black,
@@ -1115,7 +2032,8 @@ impl Not for PieceColor {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChessResult {
     Ongoing,
     WhiteWon,
@@ -1123,6 +2041,23 @@ pub enum ChessResult {
     Draw,
 }
 
+// why a game ended
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    InsufficientMaterial,
+    // a rule of the variant, such as the king reaching the hill
+    VariantRule,
+    // declare_draw() or declare_win(), for agreed draws and resignations
+    Declared,
+    // a player ran out of time, see flag()
+    TimeForfeit,
+}
+
 // makes a line from start (exclusive) until it collides with edge or a piece marked in own_color_bitmap or after colliding with a piece marked in other_color_bitmap
 fn bitmap_line(start: Square,  dx: i8, dy: i8, own_color_bitmap: u64, other_color_bitmap: u64) -> u64 {
     let mut square = start;
@@ -1149,7 +2084,7 @@ fn _print_bitmap(bitmap: u64) {
 
 fn _make_color_bitmap(live_pieces: &HashMap<Square, Piece>, color: PieceColor) -> u64 {
     let mut bitmap = 0;
-    for (_, piece) in live_pieces {
+    for piece in live_pieces.values() {
         if piece.color != color {
             continue;
         }
@@ -1170,6 +2105,8 @@ struct BoardValue {
     rook_bitmap: u64,
     pawn_bitmap: u64,
     data: u8, // first bit is if en passant was possible, second = left (long) castle permission, third = right (short) castle permission, +4 for black
+    checks: (u32, u32), // checks given in Three-check
+    pockets: [u8; 10], // number of each piece in the Crazyhouse pockets, white first
 }
 
 impl From<&Game> for BoardValue {
@@ -1185,14 +2122,8 @@ impl From<&Game> for BoardValue {
         let mut data = 0;
 
         let live_pieces = game.get_board_state();
-        let en_passant_x = match live_pieces.get(&game.last_moved_to) {
-            Some(pawn) => {
-                if pawn.piece_type == PieceType::Pawn && game.last_moved_from.y + pawn.get_direction() * 2 == game.last_moved_to.y {
-                    game.last_moved_to.x
-                } else {
-                    -8
-                }
-            },
+        let en_passant_x = match game.double_step_pawn() {
+            Some(pawn) => pawn.pos.x,
             None => -8,
         };
 
@@ -1216,23 +2147,18 @@ impl From<&Game> for BoardValue {
                 PieceColor::Black => 4,
             };
 
-            if piece.piece_type == PieceType::King && !piece.has_moved {
-                data |= 0b0110 << color_bitshift;
-
-                // left castle
-                if match live_pieces.get(&Square::from((0, square.y))) { // if has moved
-                    Some(rook) => rook.piece_type != PieceType::Rook || rook.has_moved,
-                    None => true,
-                } {
-                    data &= !(0b0010 << color_bitshift);
+            if piece.piece_type == PieceType::King {
+                // castle permissions
+                let rights = game.castling_rights();
+                let (short, long) = match piece.color {
+                    PieceColor::White => (rights[0], rights[1]),
+                    PieceColor::Black => (rights[2], rights[3]),
+                };
+                if long {
+                    data |= 0b0010 << color_bitshift;
                 }
-
-                // right castle
-                if match live_pieces.get(&Square::from((7, square.y))) { // if has moved
-                    Some(rook) => rook.piece_type != PieceType::Rook || rook.has_moved,
-                    None => true,
-                } {
-                    data &= !(0b0100 << color_bitshift);
+                if short {
+                    data |= 0b0100 << color_bitshift;
                 }
             } else if piece.piece_type == PieceType::Pawn && piece.pos.y == match piece.color {
                 PieceColor::White => 4,
@@ -1245,7 +2171,14 @@ impl From<&Game> for BoardValue {
             }
         }
 
-        Self { white_bitmap, black_bitmap, king_bitmap, queen_bitmap, bishop_bitmap, knight_bitmap, rook_bitmap, pawn_bitmap, data }
+        let checks = (game.white_checks, game.black_checks);
+        let mut pockets = [0; 10];
+        for (i, piece_type) in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn].iter().enumerate() {
+            pockets[i] = game.white_pocket.iter().filter(|p| *p == piece_type).count() as u8;
+            pockets[i + 5] = game.black_pocket.iter().filter(|p| *p == piece_type).count() as u8;
+        }
+
+        Self { white_bitmap, black_bitmap, king_bitmap, queen_bitmap, bishop_bitmap, knight_bitmap, rook_bitmap, pawn_bitmap, data, checks, pockets }
     }
 }
 
@@ -1266,49 +2199,98 @@ mod tests {
     fn test_game() {
         let mut game = Game::new();
         assert!(game.turn == PieceColor::White);
-        assert!(game.do_move(&Square::from("E2"), &Square::from("E4")) == true); // (white) pawn opening
+        assert!(game.do_move(&Square::E2, &Square::E4)); // (white) pawn opening
         assert!(game.turn == PieceColor::Black);
-        assert!(game.do_move(&Square::from("D7"), &Square::from("D5")) == true); // (black) queen side pawn
+        assert!(game.do_move(&Square::D7, &Square::D5)); // (black) queen side pawn
 
-        assert!(game.do_move(&Square::from("E4"), &Square::from("D5")) == true); // (white) take pawn
-        assert!(game.capture == true);
-        assert!(game.do_move(&Square::from("C8"), &Square::from("E6")) == true); // (black) move bishop
+        assert!(game.do_move(&Square::E4, &Square::D5)); // (white) take pawn
+        assert!(game.capture);
+        assert!(game.do_move(&Square::C8, &Square::E6)); // (black) move bishop
         
-        assert!(game.do_move(&Square::from("D1"), &Square::from("F3")) == true); // (white) move queen
-        assert!(game.do_move(&Square::from("B8"), &Square::from("D7")) == true); // (black) move knight
+        assert!(game.do_move(&Square::D1, &Square::F3)); // (white) move queen
+        assert!(game.do_move(&Square::B8, &Square::D7)); // (black) move knight
         
-        assert!(game.do_move(&Square::from("D5"), &Square::from("E6")) == true); // (white) take bishop with pawn
-        assert!(game.capture == true);
-        assert!(game.do_move(&Square::from("A7"), &Square::from("A5")) == true); // (black) move A-pawn
-        assert!(game.capture == false);
+        assert!(game.do_move(&Square::D5, &Square::E6)); // (white) take bishop with pawn
+        assert!(game.capture);
+        assert!(game.do_move(&Square::A7, &Square::A5)); // (black) move A-pawn
+        assert!(!game.capture);
         
-        assert!(game.do_move(&Square::from("A2"), &Square::from("A3")) == true); // (white) move A-pawn
-        assert!(game.do_move(&Square::from("A5"), &Square::from("A4")) == true); // (black) move A-pawn
+        assert!(game.do_move(&Square::A2, &Square::A3)); // (white) move A-pawn
+        assert!(game.do_move(&Square::A5, &Square::A4)); // (black) move A-pawn
         
-        assert!(game.do_move(&Square::from("B2"), &Square::from("B4")) == true); // (white) move B-pawn
-        assert!(game.do_move(&Square::from("A4"), &Square::from("A3")) == false); // (black) cant move forward
-        assert!(game.do_move(&Square::from("A4"), &Square::from("B3")) == true); // (black) en passant
-        assert!(game.capture == true);
+        assert!(game.do_move(&Square::B2, &Square::B4)); // (white) move B-pawn
+        assert!(!game.do_move(&Square::A4, &Square::A3)); // (black) cant move forward
+        assert!(game.do_move(&Square::A4, &Square::B3)); // (black) en passant
+        assert!(game.capture);
         
-        assert!(game.do_move(&Square::from("F3"), &Square::from("F7")) == true); // (white) move queen to mate
+        assert!(game.do_move(&Square::F3, &Square::F7)); // (white) move queen to mate
         assert!(game.result == ChessResult::WhiteWon);
     }
 
     #[test]
     fn test_fen() {
         let fen = "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 99 50";
-        let mut game = Game::from_fen(&fen.to_string());
+        let mut game = Game::from_fen(fen);
 
         assert!(game.to_fen() == fen);
-        assert!(game.do_move(&Square::from("f7"), &Square::from("F6")) == true);
+        assert!(game.do_move(&Square::F7, &Square::F6));
         assert!(game.result == ChessResult::Draw);
-        assert!(game.to_fen() == "8/8/3p1k2/1p1Pp2p/pP2Pp1P/P4P1K/8/8 w - - 100 51")
+        assert!(game.to_fen() == "8/8/3p1k2/1p1Pp2p/pP2Pp1P/P4P1K/8/8 w - - 100 51");
+
+        // the en passant field is the square behind the pawn
+        let mut game = Game::new();
+        assert!(game.do_move(&Square::E2, &Square::E4));
+        assert!(game.to_fen() == "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let mut game = Game::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert!(game.to_fen() == "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert!(game.get_moves_bitmap(&Square::E5) == Square::E6.to_bitmap() | Square::F6.to_bitmap());
+        assert!(game.do_move(&Square::E5, &Square::F6));
+        assert!(game.capture && !game.get_board_state().contains_key(&Square::F5));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(game.start_fen(), "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(game.do_move(&Square::B7, &Square::B8));
+        assert!(game.history()[0].promotion.is_none());
+        assert!(game.pawn_promotion(PieceType::Rook));
+        assert!(game.make_move(&Move::from_uci("e8d7").unwrap()));
+        let history: Vec<String> = game.history().iter().map(|m| m.to_uci()).collect();
+        assert_eq!(history, vec!["b7b8r", "e8d7"]);
+        assert_eq!(Game::new().start_fen(), Game::new().to_fen());
+    }
+
+    #[test]
+    fn test_all_moves() {
+        let game = Game::new();
+        assert_eq!(game.get_all_moves().len(), 20);
+
+        // promotions are listed once for every piece type
+        let mut game = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1");
+        let moves = game.get_all_moves();
+        assert_eq!(moves.iter().filter(|m| m.promotion.is_some()).count(), 4);
+
+        // a promotion needs the piece type
+        assert!(!game.make_move(&Move::new(Square::E7, Square::E8)));
+        assert!(game.make_move(&Move::from_uci("e7e8n").unwrap()));
+        assert_eq!(game.get_board_state().get(&Square::E8).unwrap().piece_type, PieceType::Knight);
+        assert_eq!(game.turn, PieceColor::Black);
+
+        assert_eq!(Move::from_uci("e7e8q").unwrap().to_uci(), "e7e8q");
+        assert_eq!(Move::from_uci("g1f3").unwrap(), Move::new(Square::G1, Square::F3));
+        assert!(Move::from_uci("e7e9").is_none());
+        assert!(Move::from_uci("e7e8p").is_none());
+        assert_eq!(Move::from_uci("e7e8k").unwrap().promotion, Some(PieceType::King));
+        assert_eq!(Move::from_uci("n@f3").unwrap(), Move::new_drop(PieceType::Knight, Square::F3));
+        assert_eq!(Move::from_uci("P@e4").unwrap().to_uci(), "P@e4");
+        assert!(Move::from_uci("K@e4").is_none());
     }
 
     #[test]
     fn test_piece() {
-        let piece1 = Piece { piece_type: PieceType::Knight, color: PieceColor::White, pos: Square::from((0,3)), has_moved: false };
-        let piece2 = Piece { piece_type: PieceType::Bishop, color: PieceColor::Black, pos: Square::from((3,0)), has_moved: false };
+        let piece1 = Piece { piece_type: PieceType::Knight, color: PieceColor::White, pos: Square::at(0,3), has_moved: false };
+        let piece2 = Piece { piece_type: PieceType::Bishop, color: PieceColor::Black, pos: Square::at(3,0), has_moved: false };
         
         assert!(piece1.get_direction() == 1);
         assert!(piece2.get_direction() == -1);
@@ -1317,9 +2299,9 @@ mod tests {
     #[test]
     fn test_square() { // and bitmap_line()
         let square0 = Square {x: 3, y: 0};
-        let square1 = Square::from(46); // (6, 5)
-        let square2 = Square::from((4, 5));
-        let square3 = Square::from("B3");
+        let square1 = Square::from_index(46).unwrap(); // (6, 5)
+        let square2 = Square::from_tuple((4, 5)).unwrap();
+        let square3 = Square::from_notation("B3").unwrap();
         
         assert!(square0.x == 3);
         assert!(square0.y == 0);
@@ -1343,6 +2325,30 @@ mod tests {
         assert!(square2.to_tuple() == (4,5));
         assert!(square3.to_notation() == "B3");
 
+        // squares outside the board can not be created
+        assert!(Square::from_index(64).is_none() && Square::from_index(-1).is_none());
+        assert!(Square::from_tuple((8, 0)).is_none() && Square::from_tuple((0, -1)).is_none());
+        assert!(Square::from_notation("Z9").is_none() && Square::from_notation("E").is_none());
+
+        assert_eq!(Square::new(File::E, Rank::Fourth), Square::E4);
+        assert!(Square::E4.file() == File::E && Square::E4.rank() == Rank::Fourth);
+        assert_eq!(Square::all().count(), 64);
+        assert!(Square::all().enumerate().all(|(i, square)| square.to_index() == i as i8));
+        assert!(!Square::A1.is_light() && Square::H1.is_light());
+
+        assert!(Square::A1.distance(&Square::H8) == 7 && Square::E4.distance(&Square::F6) == 2);
+        assert!(Square::C1.same_diagonal(&Square::H6) && Square::H1.same_diagonal(&Square::A8));
+        assert!(!Square::C1.same_diagonal(&Square::C3));
+        assert!(Square::E2.same_file(&Square::E7) && Square::A3.same_rank(&Square::H3));
+
+        // no square is off the board, not even the last move before the first move
+        let mut game = Game::new();
+        assert!(game.last_moved_from.is_none() && game.last_moved_to.is_none());
+        assert!(game.do_move(&Square::E2, &Square::E4));
+        assert!(game.last_moved_from.unwrap().file() == File::E && game.last_moved_to == Some(Square::E4));
+        let game = Game::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+        assert!(game.last_moved_from == Some(Square::E7) && game.last_moved_to == Some(Square::E5));
+
         assert!(bitmap_line(square0,  1, 0, 0, 0) == 0b11110000);
         assert!(bitmap_line(square0, -1, 0, 0, 0) == 0b00000111);
         assert!(bitmap_line(square0, -1, 0, 0b10, 0) == 0b00000100);
@@ -1363,6 +2369,87 @@ mod tests {
         assert!(!(col1 != col2));
     }
 
+    #[test]
+    fn test_display_from_str() {
+        let square: Square = "e4".parse().unwrap();
+        assert_eq!(square, Square::at(4, 3));
+        assert_eq!(square.to_string(), "e4");
+        assert!("z9".parse::<Square>().is_err());
+        assert!("e".parse::<Square>().is_err());
+        assert!("e44".parse::<Square>().is_err());
+
+        let m: Move = "e7e8q".parse().unwrap();
+        assert_eq!(m.promotion, Some(PieceType::Queen));
+        assert_eq!(m.to_string(), "e7e8q");
+        assert!("e7".parse::<Move>().is_err());
+
+        assert_eq!("n".parse::<PieceType>(), Ok(PieceType::Knight));
+        assert_eq!("Knight".parse::<PieceType>(), Ok(PieceType::Knight));
+        assert!("x".parse::<PieceType>().is_err());
+
+        let game: Game = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".parse().unwrap();
+        assert_eq!(game.to_string().lines().nth(4), Some("4 . . . . P . . ."));
+        assert_eq!(game.to_string().lines().last(), Some("  a b c d e f g h"));
+        assert_eq!(game.live_pieces[&square].to_string(), "P");
+        assert!(format!("{:?}", game).contains("e3"));
+
+        assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1".parse::<Game>().is_err());
+        assert!("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse::<Game>().is_err());
+        assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1".parse::<Game>().is_err());
+        assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1".parse::<Game>().is_err());
+        assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1".parse::<Game>().is_err());
+
+        // the syntax of the variants is only read with the variant
+        let crazyhouse = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/Q~NBQKBNR[Pn] w Kkq - 0 1";
+        assert!(crazyhouse.parse::<Game>().unwrap_err().contains("Crazyhouse"));
+        let game = Game::from_str_variant(crazyhouse, Variant::Crazyhouse).unwrap();
+        assert_eq!(game.to_fen(), crazyhouse);
+        let three_check = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +1+2";
+        assert!(three_check.parse::<Game>().unwrap_err().contains("Three-check"));
+        assert_eq!(Game::from_str_variant(three_check, Variant::ThreeCheck).unwrap().black_checks, 2);
+        assert!(Game::from_str_variant(three_check, Variant::Crazyhouse).is_err());
+    }
+
+    #[test]
+    fn test_chess960() {
+        assert_eq!(Game::new_chess960(518).unwrap().to_fen(), Game::new().to_fen());
+        assert!(Game::new_chess960(960).is_err());
+
+        // all positions are different, have the king between the rooks and the bishops on different colors
+        let mut back_ranks = std::collections::HashSet::new();
+        for i in 0..960 {
+            let fen = Game::new_chess960(i).unwrap().to_fen();
+            let back_rank = fen.split('/').next().unwrap().to_string();
+            let king = back_rank.find('k').unwrap();
+            let rooks: Vec<usize> = back_rank.match_indices('r').map(|(x, _)| x).collect();
+            let bishops: Vec<usize> = back_rank.match_indices('b').map(|(x, _)| x).collect();
+            assert!(rooks[0] < king && king < rooks[1]);
+            assert!(bishops[0] % 2 != bishops[1] % 2);
+            back_ranks.insert(back_rank);
+        }
+        assert_eq!(back_ranks.len(), 960);
+
+        // Shredder-FEN and X-FEN
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let game = Game::from_fen(fen);
+        assert!(game.chess960);
+        assert_eq!(game.to_shredder_fen(), fen);
+        assert_eq!(game.to_fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+        assert_eq!(perft(game, 3), 12189);
+
+        // castling is written as the king taking its own rook, the king ends on g1 and the rook on f1
+        let mut game = Game::from_fen("1r2k2r/8/8/8/8/8/8/1R2K1R1 w GBhb - 0 1");
+        assert!(game.chess960);
+        assert!(game.get_moves_list(&Square::E1).contains(&Square::G1));
+        assert!(game.make_move(&Move::from_uci("e1g1").unwrap()));
+        assert_eq!(game.to_fen(), "1r2k2r/8/8/8/8/8/8/1R3RK1 b kq - 1 1");
+
+        // long castle with the king passing an attacked square is not allowed
+        let game = Game::from_fen("1r1rk3/8/8/8/8/8/8/1R2K1R1 w GB - 0 1");
+        assert!(!game.get_moves_list(&Square::E1).contains(&Square::B1));
+        assert!(game.get_moves_list(&Square::E1).contains(&Square::G1));
+    }
+
     #[test]
     fn test_perft() {
         let position = 2;
@@ -1395,7 +2482,7 @@ mod tests {
         }
 
         for i in 0..64 {
-            let from = Square::from(i);
+            let from = Square::at_index(i);
             let moves = game.get_moves_list(&from);
             for to in moves {
                 let mut game2 = game.clone();
@@ -0,0 +1,208 @@
+// Packed 32 byte positions, for hashing, finding duplicates and dumping training data.
+//
+// layout:
+//  0-7    occupancy, a bitmap of the squares with a piece (little endian, bit 0 is A1)
+//  8-23   a 4 bit code for each piece in the order of the occupancy bits, the low half of a byte first
+//         1-6 white pawn, knight, bishop, rook, queen, king, 9-14 the same for black
+//  24     bit 0 is set if black is to move, bit 1 in Chess960
+//  25-26  castling rights, 4 bits each for white short, white long, black short, black long (little endian)
+//         0 if the right is gone, otherwise the file of the rook + 1
+//  27     en passant, the file of the target square + 1 or 0
+//  28     halfmove clock, at most 255
+//  29-30  fullmove number (little endian)
+//  31     the variant in the low 4 bits, see variant::VARIANTS, then the checks given by white and black in Three-check
+//         (2 bits each)
+//
+// Crazyhouse pockets and promoted pieces do not fit, neither do positions with more than 32 pieces (Horde).
+
+use crate::variant::{Variant, VARIANTS};
+use crate::{Game, PieceColor, PieceType, Square};
+
+const PIECE_TYPES: [PieceType; 6] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King];
+
+impl Game {
+    // returns the position packed into 32 bytes, see the top of packed.rs
+    // None if there are more than 32 pieces, pieces in the Crazyhouse pockets or promoted pieces
+    pub fn to_packed(&self) -> Option<[u8; 32]> {
+        if self.live_pieces.len() > 32 || !self.white_pocket.is_empty() || !self.black_pocket.is_empty() || self.promoted != 0 {
+            return None;
+        }
+
+        let mut packed = [0; 32];
+
+        let occupancy = self.live_pieces.keys().fold(0, |bitmap, square| bitmap | square.to_bitmap());
+        packed[0..8].copy_from_slice(&occupancy.to_le_bytes());
+
+        let mut i = 0;
+        for index in 0..64 {
+            if occupancy & (1 << index) == 0 {
+                continue;
+            }
+            let piece = &self.live_pieces[&Square::at(index % 8, index / 8)];
+            let mut code = PIECE_TYPES.iter().position(|t| *t == piece.piece_type).unwrap() as u8 + 1;
+            if piece.color == PieceColor::Black {
+                code |= 8;
+            }
+            packed[8 + i / 2] |= code << (4 * (i % 2));
+            i += 1;
+        }
+
+        if self.turn == PieceColor::Black {
+            packed[24] |= 1;
+        }
+        if self.chess960 {
+            packed[24] |= 2;
+        }
+
+        let mut castling: u16 = 0;
+        for (i, color) in [PieceColor::White, PieceColor::Black].iter().enumerate() {
+            if let Some(king) = self.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color == *color) {
+                for (j, short) in [true, false].iter().enumerate() {
+                    if let Some(rook) = self.castling_rook(king, *short) {
+                        castling |= (rook.x as u16 + 1) << (4 * (i * 2 + j));
+                    }
+                }
+            }
+        }
+        packed[25..27].copy_from_slice(&castling.to_le_bytes());
+
+        packed[27] = self.en_passant_square().map(|square| square.x as u8 + 1).unwrap_or(0);
+        packed[28] = self.fifty_move_rule.min(255) as u8;
+        packed[29..31].copy_from_slice(&(self.fullmoves.min(u16::MAX as u32) as u16).to_le_bytes());
+        packed[31] = VARIANTS.iter().position(|v| *v == self.variant).unwrap() as u8
+            | (self.white_checks.min(3) as u8) << 4 | (self.black_checks.min(3) as u8) << 6;
+
+        Some(packed)
+    }
+
+    // creates a game from a position packed by to_packed()
+    pub fn from_packed(packed: &[u8; 32]) -> Result<Self, String> {
+        let occupancy = u64::from_le_bytes(packed[0..8].try_into().unwrap());
+        if occupancy.count_ones() > 32 {
+            return Err("more than 32 pieces".to_string());
+        }
+
+        let mut board = [None; 64];
+        let mut i = 0;
+        for (index, square) in board.iter_mut().enumerate() {
+            if occupancy & (1 << index) == 0 {
+                continue;
+            }
+            let code = (packed[8 + i / 2] >> (4 * (i % 2))) & 0xF;
+            let piece_type = match PIECE_TYPES.get(((code & 7) as usize).wrapping_sub(1)) {
+                Some(piece_type) => *piece_type,
+                None => return Err(format!("unknown piece code {}", code)),
+            };
+            let c = piece_type.to_char();
+            *square = Some(if code & 8 != 0 { c.to_ascii_lowercase() } else { c });
+            i += 1;
+        }
+
+        let mut fen = String::new();
+        for y in (0..8).rev() {
+            let mut empty = 0;
+            for x in 0..8 {
+                match board[y * 8 + x] {
+                    Some(c) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(c);
+                    },
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if y > 0 {
+                fen.push('/');
+            }
+        }
+
+        let black = packed[24] & 1 != 0;
+        fen.push_str(if black { " b " } else { " w " });
+
+        // castling is written as Shredder-FEN so the rook files are kept
+        let castling = u16::from_le_bytes([packed[25], packed[26]]);
+        let mut field = String::new();
+        for i in 0..4 {
+            let file = (castling >> (4 * i)) & 0xF;
+            if file > 8 {
+                return Err(format!("unknown castling file {}", file));
+            } else if file > 0 {
+                let c = (b'A' + file as u8 - 1) as char;
+                field.push(if i < 2 { c } else { c.to_ascii_lowercase() });
+            }
+        }
+        fen.push_str(if field.is_empty() { "-" } else { &field });
+
+        // the target square is behind the pawn of the side that just moved
+        match packed[27] {
+            0 => fen.push_str(" -"),
+            file @ 1..=8 => fen.push_str(&format!(" {}{}", (b'a' + file - 1) as char, if black { 3 } else { 6 })),
+            file => return Err(format!("unknown en passant file {}", file)),
+        }
+
+        fen.push_str(&format!(" {} {}", packed[28], u16::from_le_bytes([packed[29], packed[30]])));
+
+        let variant = *VARIANTS.get((packed[31] & 0xF) as usize).ok_or("unknown variant")?;
+        if variant == Variant::ThreeCheck {
+            fen.push_str(&format!(" +{}+{}", (packed[31] >> 4) & 3, packed[31] >> 6));
+        }
+
+        let mut game = Game::from_fen_variant(&fen, variant);
+        game.chess960 = packed[24] & 2 != 0;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn test_packed() {
+        let mut game = Game::new();
+        let packed = game.to_packed().unwrap();
+        assert_eq!(Game::from_packed(&packed).unwrap().to_fen(), game.to_fen());
+        // the first rank from A1, the codes of two pieces in each byte
+        assert_eq!(packed[8..12], [0x24, 0x53, 0x36, 0x42]);
+
+        assert!(game.make_move(&Move::from_uci("e2e4").unwrap()));
+        assert_eq!(Game::from_packed(&game.to_packed().unwrap()).unwrap().to_fen(), game.to_fen());
+        for uci in ["g8f6", "e4e5", "d7d5", "e1e2"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let copy = Game::from_packed(&game.to_packed().unwrap()).unwrap();
+        assert!(copy.to_fen() == "rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3" && copy.to_fen() == game.to_fen());
+
+        let game = Game::new_chess960(0).unwrap();
+        let copy = Game::from_packed(&game.to_packed().unwrap()).unwrap();
+        assert!(copy.chess960 && copy.to_fen() == game.to_fen());
+        let game = Game::from_fen_variant("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+1", Variant::ThreeCheck);
+        assert_eq!(Game::from_packed(&game.to_packed().unwrap()).unwrap().to_fen(), game.to_fen());
+
+        // the same position gives the same bytes, however it was reached
+        let mut a = Game::new();
+        let mut b = Game::new();
+        for uci in ["g1f3", "g8f6", "b1c3"] {
+            a.make_move(&Move::from_uci(uci).unwrap());
+        }
+        for uci in ["b1c3", "g8f6", "g1f3"] {
+            b.make_move(&Move::from_uci(uci).unwrap());
+        }
+        assert_eq!(a.to_packed(), b.to_packed());
+
+        assert!(Game::new_variant(Variant::Horde).to_packed().is_none());
+        // a promoted queen would come back as a queen when it is captured
+        let game = Game::from_fen_variant("Q~3k3/8/8/8/8/8/8/4K3[] b - - 0 1", Variant::Crazyhouse);
+        assert!(game.to_packed().is_none());
+        assert!(Game::new_variant(Variant::Crazyhouse).to_packed().is_some());
+        let mut broken = packed;
+        broken[8] = 0x07;
+        assert!(Game::from_packed(&broken).is_err());
+    }
+}
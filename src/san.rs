@@ -0,0 +1,125 @@
+// Standard algebraic notation (SAN) such as "Nf3", "exd5", "O-O", "e8=Q+" or "N@f3" for Crazyhouse drops.
+
+use crate::{ChessResult, Game, Move, PieceType};
+
+impl Game {
+    // returns the move in SAN, None if it is not a legal move
+    pub fn move_to_san(&self, m: &Move) -> Option<String> {
+        let mut san = self.san_without_suffix(m)?;
+
+        let mut after = self.clone();
+        after.make_move(m);
+        if after.check {
+            let mate = after.result == ChessResult::WhiteWon || after.result == ChessResult::BlackWon;
+            san.push(if mate { '#' } else { '+' });
+        }
+
+        Some(san)
+    }
+
+    // returns the legal move written in SAN, checks, annotations and 0-0 for castling are accepted, None if no move matches
+    pub fn move_from_san(&self, san: &str) -> Option<Move> {
+        let mut san: String = san.trim().chars().filter(|c| !"+#!?=".contains(*c)).collect();
+        san = san.replace('0', "O");
+        // pawn drops can be written without the letter
+        if san.starts_with('@') {
+            san.insert(0, 'P');
+        }
+
+        self.get_all_moves().into_iter().find(|m| {
+            self.san_without_suffix(m).is_some_and(|s| s.replace('=', "") == san)
+        })
+    }
+
+    // SAN without the check or mate sign
+    pub(crate) fn san_without_suffix(&self, m: &Move) -> Option<String> {
+        let moves = self.get_all_moves();
+        if !moves.contains(m) {
+            return None;
+        }
+
+        let to = m.to.to_notation().to_ascii_lowercase();
+        if let Some(piece_type) = m.drop {
+            return Some(format!("{}@{}", piece_type.to_char(), to));
+        }
+
+        let piece = self.live_pieces.get(&m.from)?;
+        let from = m.from.to_notation().to_ascii_lowercase();
+
+        if piece.piece_type == PieceType::King {
+            if let Some(rook) = self.castling_rook_for_move(piece, m.to) {
+                return Some(if rook.x > piece.pos.x { "O-O" } else { "O-O-O" }.to_string());
+            }
+        }
+
+        let capture = self.live_pieces.get(&m.to).is_some_and(|p| p.color != piece.color);
+        let mut san = String::new();
+
+        if piece.piece_type == PieceType::Pawn {
+            // en passant captures land on an empty square
+            if capture || m.from.x != m.to.x {
+                san.push_str(&from[..1]);
+                san.push('x');
+            }
+            san.push_str(&to);
+            if let Some(promotion) = m.promotion {
+                san.push('=');
+                san.push(promotion.to_char());
+            }
+            return Some(san);
+        }
+
+        san.push(piece.piece_type.to_char());
+
+        // other pieces of the same type that can move to the same square
+        let others: Vec<&Move> = moves.iter().filter(|other| {
+            other.drop.is_none() && other.to == m.to && other.from != m.from
+                && self.live_pieces.get(&other.from).is_some_and(|p| p.piece_type == piece.piece_type)
+        }).collect();
+        if !others.is_empty() {
+            if others.iter().all(|other| other.from.x != m.from.x) {
+                san.push_str(&from[..1]);
+            } else if others.iter().all(|other| other.from.y != m.from.y) {
+                san.push_str(&from[1..]);
+            } else {
+                san.push_str(&from);
+            }
+        }
+
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&to);
+        Some(san)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_san() {
+        let mut game = Game::new();
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "0-0", "Bg4"] {
+            let m = game.move_from_san(san).unwrap();
+            assert!(game.make_move(&m));
+        }
+        assert_eq!(game.move_to_san(&Move::from_uci("f1e1").unwrap()).unwrap(), "Re1");
+        assert_eq!(game.move_from_san("Qe2!?").unwrap().to_uci(), "d1e2");
+        assert!(game.move_from_san("Nf6").is_none());
+
+        // disambiguation, promotion and mate
+        let game = Game::from_fen("6k1/1P6/8/8/8/8/8/R3K2R w KQ - 0 1");
+        assert_eq!(game.move_to_san(&Move::from_uci("a1d1").unwrap()).unwrap(), "Rd1");
+        assert_eq!(game.move_to_san(&Move::from_uci("e1g1").unwrap()).unwrap(), "O-O");
+        assert_eq!(game.move_to_san(&Move::from_uci("b7b8q").unwrap()).unwrap(), "b8=Q+");
+        assert_eq!(game.move_to_san(&Move::from_uci("a1a8").unwrap()).unwrap(), "Ra8+");
+        let game = Game::from_fen("6k1/8/8/8/8/8/4K3/R6R w - - 0 1");
+        assert_eq!(game.move_to_san(&Move::from_uci("a1d1").unwrap()).unwrap(), "Rad1");
+        let game = Game::from_fen("6k1/8/8/8/8/R7/4K3/R7 w - - 0 1");
+        assert_eq!(game.move_to_san(&Move::from_uci("a1a2").unwrap()).unwrap(), "R1a2");
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert_eq!(game.move_to_san(&Move::from_uci("a1a8").unwrap()).unwrap(), "Ra8#");
+    }
+}
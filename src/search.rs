@@ -0,0 +1,763 @@
+// Alpha-beta search with iterative deepening, quiescence search and a transposition table.
+//
+// The search scales over several cores using Lazy SMP: every thread searches the same root position on its own copy
+// of the game and they only cooperate through the shared transposition table. Helper threads start at different
+// depths so they fill the table with results the main thread can use. The result of the main thread is returned.
+// With one thread (the default) the search is deterministic for a given depth, which is what the tests rely on.
+//
+// The playing strength can be lowered and the style changed with options, see the strength module.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::eval::{self, EvalParams};
+use crate::strength::{self, Personality, Strength, MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+use crate::tablebase::{Tablebase, Wdl};
+use crate::tt::{Bound, TranspositionTable, TtEntry, MATE_BOUND, MATE_SCORE};
+use crate::{ChessResult, Game, Move, PieceColor, PieceType};
+
+const DEFAULT_HASH_MB: usize = 16;
+const MAX_THREADS: usize = 256;
+const MAX_DEPTH: u32 = 64;
+const INFINITY: i32 = MATE_SCORE + 1;
+// nodes a thread counts before adding them to the shared count
+const NODE_BATCH: u64 = 1024;
+// score of a tablebase win, below the mate scores
+const TB_WIN_SCORE: i32 = MATE_BOUND - 1000;
+
+// limits for a search, the search stops as soon as any of them is reached
+// with no limits at all the search runs until Engine::stop() is called or MAX_DEPTH is reached
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        Self { depth: Some(depth), ..Default::default() }
+    }
+
+    pub fn movetime(movetime: Duration) -> Self {
+        Self { movetime: Some(movetime), ..Default::default() }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    // None if there are no legal moves
+    pub best_move: Option<Move>,
+    // centipawns from the point of view of the side to move, see is_mate_score()
+    pub score: i32,
+    // last fully searched depth
+    pub depth: u32,
+    // nodes searched by all threads
+    pub nodes: u64,
+    // principal variation, starts with best_move
+    // when a lowered strength plays a random move it is only that move
+    pub pv: Vec<Move>,
+}
+
+// returns true if the score means that one side can force mate
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_BOUND
+}
+
+// returns the number of moves (not half-moves) to mate, negative if the side to move gets mated
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+    let plies = MATE_SCORE - score.abs();
+    Some(if score > 0 { (plies + 1) / 2 } else { -(plies + 1) / 2 })
+}
+
+pub struct Engine {
+    tt: TranspositionTable,
+    params: EvalParams,
+    threads: usize,
+    stop: AtomicBool,
+
+    // strength and style
+    skill_level: u32,
+    limit_strength: bool,
+    elo: u32,
+    personality: Personality,
+    // used for evaluation noise and blunders
+    seed: u64,
+
+    // endgames are played from the tablebase when one is set
+    tablebase: Option<Tablebase>,
+}
+
+// the settings, the transposition table and tablebase are summed up
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Engine")
+            .field("threads", &self.threads)
+            .field("hash_bytes", &self.tt.size_bytes())
+            .field("strength", &self.strength())
+            .field("personality", &self.personality)
+            .field("tablebase", &self.tablebase)
+            .finish()
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            tt: TranspositionTable::new(DEFAULT_HASH_MB),
+            params: EvalParams::default(),
+            threads: 1,
+            stop: AtomicBool::new(false),
+            skill_level: MAX_SKILL_LEVEL,
+            limit_strength: false,
+            elo: MAX_ELO,
+            personality: Personality::Balanced,
+            seed: match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(time) => time.as_nanos() as u64,
+                Err(_) => 0,
+            },
+            tablebase: None,
+        }
+    }
+
+    // sets an option by name, like the setoption command of UCI
+    //  "Threads" number of search threads (1 or more)
+    //  "Hash"    size of the transposition table in MB, clears it
+    //  "Skill Level"       0-20, lower is weaker, 20 is full strength
+    //  "UCI_LimitStrength" true to play at the rating set by UCI_Elo instead of the skill level
+    //  "UCI_Elo"           rating to play at, 400-2400
+    //  "Personality"       Balanced, Aggressive, Positional or Materialistic
+    //  "Seed"              seed for the randomness of weaker levels, the same seed gives the same moves
+    //  "SyzygyPath"        directory with Syzygy tablebase files, empty to not use tablebases
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "Skill Level" => match value.trim().parse::<u32>() {
+                Ok(level) if level <= MAX_SKILL_LEVEL => self.skill_level = level,
+                _ => return Err(format!("Skill Level must be a number between 0 and {}", MAX_SKILL_LEVEL)),
+            },
+            "UCI_LimitStrength" => match value.trim() {
+                "true" => self.limit_strength = true,
+                "false" => self.limit_strength = false,
+                _ => return Err("UCI_LimitStrength must be true or false".to_string()),
+            },
+            "UCI_Elo" => match value.trim().parse::<u32>() {
+                Ok(elo) if (MIN_ELO..=MAX_ELO).contains(&elo) => self.elo = elo,
+                _ => return Err(format!("UCI_Elo must be a number between {} and {}", MIN_ELO, MAX_ELO)),
+            },
+            "Personality" => match Personality::from_name(value) {
+                Some(personality) => self.personality = personality,
+                None => return Err("Personality must be Balanced, Aggressive, Positional or Materialistic".to_string()),
+            },
+            "Seed" => match value.trim().parse::<u64>() {
+                Ok(seed) => self.seed = seed,
+                Err(_) => return Err("Seed must be a number".to_string()),
+            },
+            "SyzygyPath" => {
+                self.tablebase = match value.trim() {
+                    "" | "<empty>" => None,
+                    path => Some(Tablebase::open(path)?),
+                };
+            },
+            "Threads" => match value.trim().parse::<usize>() {
+                Ok(threads) if (1..=MAX_THREADS).contains(&threads) => self.threads = threads,
+                _ => return Err(format!("Threads must be a number between 1 and {}", MAX_THREADS)),
+            },
+            "Hash" => match value.trim().parse::<usize>() {
+                Ok(mb) if mb > 0 => self.tt.resize(mb),
+                _ => return Err("Hash must be a positive number of MB".to_string()),
+            },
+            _ => return Err(format!("unknown option \"{}\"", name)),
+        }
+        Ok(())
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // returns the strength the engine plays at, None means full strength
+    pub fn strength(&self) -> Option<Strength> {
+        if self.limit_strength {
+            Some(Strength::from_elo(self.elo))
+        } else if self.skill_level < MAX_SKILL_LEVEL {
+            Some(Strength::from_skill_level(self.skill_level))
+        } else {
+            None
+        }
+    }
+
+    // returns the tablebase set with the SyzygyPath option
+    pub fn tablebase(&self) -> Option<&Tablebase> {
+        self.tablebase.as_ref()
+    }
+
+    pub fn personality(&self) -> Personality {
+        self.personality
+    }
+
+    // replaces the evaluation parameters
+    pub fn set_params(&mut self, params: EvalParams) {
+        self.params = params;
+    }
+
+    // clears everything that was learned from earlier searches
+    pub fn new_game(&self) {
+        self.tt.clear();
+    }
+
+    // returns the transposition table, for example to look up the stored results of positions after a search
+    pub fn tt(&self) -> &TranspositionTable {
+        &self.tt
+    }
+
+    // makes a running search stop as soon as possible, can be called from another thread
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    // searches the position and returns the best move for the side to move
+    pub fn search(&self, game: &Game, limits: &SearchLimits) -> SearchResult {
+        self.stop.store(false, Ordering::Relaxed);
+        self.tt.new_search();
+
+        let strength = self.strength();
+
+        // perfect play from the tablebase, but not when the strength is lowered
+        if let (Some(tablebase), None) = (&self.tablebase, strength) {
+            if let Some((m, result)) = tablebase.best_move(game) {
+                let score = match result.wdl {
+                    Wdl::Win => TB_WIN_SCORE - result.dtz,
+                    Wdl::Loss => -TB_WIN_SCORE - result.dtz,
+                    _ => 0,
+                };
+                return SearchResult { best_move: Some(m), score, depth: 0, nodes: 0, pv: vec![m] };
+            }
+        }
+
+        let mut limits = limits.clone();
+        if let Some(strength) = strength {
+            limits.depth = Some(limits.depth.map_or(strength.depth, |depth| depth.min(strength.depth)));
+            limits.nodes = Some(limits.nodes.map_or(strength.nodes, |nodes| nodes.min(strength.nodes)));
+        }
+
+        let shared = Shared {
+            tt: &self.tt,
+            params: self.personality.apply(&self.params),
+            draw_score: self.personality.draw_score(),
+            strength,
+            seed: self.seed,
+            stop: &self.stop,
+            nodes: AtomicU64::new(0),
+            limits,
+            start: Instant::now(),
+        };
+
+        let mut result = thread::scope(|scope| {
+            let shared = &shared;
+            // helper threads
+            for id in 1..self.threads {
+                scope.spawn(move || {
+                    let mut worker = Worker::new(shared, id);
+                    worker.iterative_deepening(game);
+                });
+            }
+
+            // main thread, stops the helpers when done
+            let mut worker = Worker::new(shared, 0);
+            let result = worker.iterative_deepening(game);
+            shared.stop.store(true, Ordering::Relaxed);
+            result
+        });
+
+        result.nodes = shared.nodes.load(Ordering::Relaxed);
+
+        // weaker levels now and then play a random move instead
+        if let Some(strength) = strength {
+            let moves = game.get_all_moves();
+            let random = strength::random(self.seed ^ game.hash());
+            if moves.len() > 1 && (random % 1000) < strength.blunder_chance as u64 {
+                let others: Vec<&Move> = moves.iter().filter(|m| Some(**m) != result.best_move).collect();
+                let blunder = *others[(strength::random(random) % others.len() as u64) as usize];
+                // the search did not look at the move, it is scored by the evaluation after it and the pv is only the move
+                let mut after = game.clone();
+                after.make_move(&blunder);
+                let worker = Worker::new(&shared, 0);
+                result.score = match after.result {
+                    ChessResult::Ongoing => -worker.evaluate(&after),
+                    _ => -worker.terminal_score(&after, 1),
+                };
+                result.best_move = Some(blunder);
+                result.pv = vec![blunder];
+            }
+        }
+
+        result
+    }
+}
+
+// state shared by all search threads
+struct Shared<'a> {
+    tt: &'a TranspositionTable,
+    params: EvalParams,
+    draw_score: i32,
+    strength: Option<Strength>,
+    seed: u64,
+    stop: &'a AtomicBool,
+    nodes: AtomicU64,
+    limits: SearchLimits,
+    start: Instant,
+}
+
+struct Worker<'a> {
+    shared: &'a Shared<'a>,
+    id: usize,
+    nodes: u64,
+    // nodes not added to the shared count yet
+    unflushed: u64,
+    // the best move of the iteration so far, for when the first iteration is stopped
+    root_best: Option<Move>,
+    // two quiet moves per ply that caused a beta cutoff
+    killers: Vec<[Option<Move>; 2]>,
+}
+
+impl<'a> Worker<'a> {
+    fn new(shared: &'a Shared<'a>, id: usize) -> Self {
+        Self { shared, id, nodes: 0, unflushed: 0, root_best: None, killers: vec![[None; 2]; MAX_DEPTH as usize + 1] }
+    }
+
+    fn iterative_deepening(&mut self, game: &Game) -> SearchResult {
+        let moves = game.get_all_moves();
+        let mut result = SearchResult { best_move: moves.first().copied(), score: 0, depth: 0, nodes: 0, pv: Vec::new() };
+        if moves.is_empty() {
+            return result;
+        }
+
+        let max_depth = self.shared.limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        // helpers skip some depths so they do not all search the same thing
+        let mut depth = 1 + (self.id % 2) as u32;
+
+        while depth <= max_depth {
+            self.root_best = None;
+            let score = self.negamax(game, depth, -INFINITY, INFINITY, 0);
+
+            if self.should_stop() {
+                // the iteration did not finish, keep the last finished one or what the first one found
+                if let (0, Some(m)) = (result.depth, self.root_best) {
+                    result.best_move = Some(m);
+                    result.score = score;
+                    result.pv = vec![m];
+                }
+                break;
+            }
+
+            result.score = score;
+            result.depth = depth;
+            result.pv = self.principal_variation(game, depth);
+            if let Some(m) = result.pv.first() {
+                result.best_move = Some(*m);
+            }
+
+            if self.should_stop() || (self.id == 0 && is_mate_score(score) && MATE_SCORE - score.abs() <= depth as i32) {
+                break;
+            }
+            depth += 1;
+        }
+
+        self.flush_nodes();
+        result
+    }
+
+    fn negamax(&mut self, game: &Game, depth: u32, mut alpha: i32, beta: i32, ply: u32) -> i32 {
+        if game.result != ChessResult::Ongoing {
+            return self.terminal_score(game, ply);
+        }
+        if depth == 0 || ply >= MAX_DEPTH {
+            return self.quiescence(game, alpha, beta, ply);
+        }
+
+        self.count_node();
+        if ply > 0 && self.should_stop() {
+            return 0;
+        }
+
+        let hash = game.hash();
+        let mut tt_move = None;
+        if let Some(entry) = self.shared.tt.probe(hash, ply) {
+            tt_move = entry.best_move;
+            if ply > 0 && entry.depth as u32 >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return entry.score,
+                    Bound::Upper if entry.score <= alpha => return entry.score,
+                    _ => (),
+                }
+            }
+        }
+
+        let mut moves = game.get_all_moves();
+        self.order_moves(game, &mut moves, tt_move, ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        for m in moves {
+            let mut child = game.clone();
+            child.make_move(&m);
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha, ply + 1);
+
+            // an unfinished search is not stored, at the root the moves searched before the stop are kept
+            if self.should_stop() {
+                return if ply == 0 && best_move.is_some() { best_score } else { 0 };
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m);
+                if ply == 0 {
+                    self.root_best = best_move;
+                }
+            }
+            if score > alpha {
+                alpha = score;
+            }
+            if alpha >= beta {
+                if !is_capture(game, &m) {
+                    let killers = &mut self.killers[ply as usize];
+                    if killers[0] != Some(m) {
+                        killers[1] = killers[0];
+                        killers[0] = Some(m);
+                    }
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.shared.tt.store(hash, ply, TtEntry { best_move, depth: depth as u8, bound, score: best_score });
+
+        best_score
+    }
+
+    // only looks at captures and promotions so the evaluation is not done in the middle of an exchange
+    fn quiescence(&mut self, game: &Game, mut alpha: i32, beta: i32, ply: u32) -> i32 {
+        if game.result != ChessResult::Ongoing {
+            return self.terminal_score(game, ply);
+        }
+
+        self.count_node();
+
+        let stand_pat = self.evaluate(game);
+        if stand_pat >= beta || ply >= MAX_DEPTH {
+            return stand_pat;
+        }
+        if stand_pat > alpha {
+            alpha = stand_pat;
+        }
+
+        let mut moves: Vec<Move> = game.get_all_moves().into_iter()
+            .filter(|m| is_capture(game, m) || m.promotion == Some(PieceType::Queen))
+            .collect();
+        self.order_moves(game, &mut moves, None, ply);
+
+        for m in moves {
+            let mut child = game.clone();
+            child.make_move(&m);
+            let score = -self.quiescence(&child, -beta, -alpha, ply + 1);
+
+            if self.should_stop() {
+                return 0;
+            }
+
+            if score >= beta {
+                return score;
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        alpha
+    }
+
+    // sorts moves so the ones most likely to be best are searched first:
+    // the transposition table move, captures of valuable pieces with cheap pieces, promotions, killer moves, the rest
+    fn order_moves(&self, game: &Game, moves: &mut [Move], tt_move: Option<Move>, ply: u32) {
+        let killers = self.killers[(ply as usize).min(MAX_DEPTH as usize)];
+        moves.sort_by_cached_key(|m| {
+            if Some(*m) == tt_move {
+                return i32::MIN;
+            }
+
+            let mut score = 0;
+            if let Some(victim) = game.live_pieces.get(&m.to) {
+                let attacker = game.live_pieces.get(&m.from).map_or(0, |p| piece_value(p.piece_type));
+                score -= 10 * piece_value(victim.piece_type) - attacker + 10000;
+            } else if is_capture(game, m) {
+                // en passant
+                score -= 10 * piece_value(PieceType::Pawn) - piece_value(PieceType::Pawn) + 10000;
+            }
+            if let Some(class) = m.promotion {
+                score -= piece_value(class) + 5000;
+            }
+            if Some(*m) == killers[0] {
+                score -= 2000;
+            } else if Some(*m) == killers[1] {
+                score -= 1000;
+            }
+            score
+        });
+    }
+
+    // follows the best moves stored in the transposition table
+    fn principal_variation(&self, game: &Game, depth: u32) -> Vec<Move> {
+        let mut pv = Vec::new();
+        let mut game = game.clone();
+        while (pv.len() as u32) < depth {
+            let m = match self.shared.tt.probe(game.hash(), 0).and_then(|entry| entry.best_move) {
+                Some(m) => m,
+                None => break,
+            };
+            if !game.get_all_moves().contains(&m) || !game.make_move(&m) {
+                break;
+            }
+            pv.push(m);
+        }
+        pv
+    }
+
+    fn evaluate(&self, game: &Game) -> i32 {
+        let score = eval::evaluate_with(game, &self.shared.params);
+        match self.shared.strength {
+            Some(strength) => score + strength.noise(game.hash(), self.shared.seed),
+            None => score,
+        }
+    }
+
+    // score of a finished game from the point of view of the side to move
+    fn terminal_score(&self, game: &Game, ply: u32) -> i32 {
+        match game.result {
+            // draw_score is for the side to move at the root
+            ChessResult::Draw | ChessResult::Ongoing => if ply.is_multiple_of(2) { self.shared.draw_score } else { -self.shared.draw_score },
+            // usually the player who just moved won, but not always, an Antichess player wins when the opponent takes
+            // their last piece
+            ChessResult::WhiteWon if game.turn == PieceColor::White => MATE_SCORE - ply as i32,
+            ChessResult::BlackWon if game.turn == PieceColor::Black => MATE_SCORE - ply as i32,
+            _ => -(MATE_SCORE - ply as i32),
+        }
+    }
+
+    // the shared count is updated in batches, adding to it on every node slows down the threads
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        self.unflushed += 1;
+        if self.unflushed == NODE_BATCH {
+            self.flush_nodes();
+        }
+    }
+
+    fn flush_nodes(&mut self) {
+        self.shared.nodes.fetch_add(self.unflushed, Ordering::Relaxed);
+        self.unflushed = 0;
+    }
+
+    fn should_stop(&self) -> bool {
+        if self.shared.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        // only the main thread checks the limits, it stops the helpers
+        if self.id != 0 {
+            return false;
+        }
+
+        let limits = &self.shared.limits;
+        let out_of_nodes = match limits.nodes {
+            Some(nodes) => self.shared.nodes.load(Ordering::Relaxed) + self.unflushed >= nodes,
+            None => false,
+        };
+        let out_of_time = match limits.movetime {
+            Some(movetime) => self.nodes.is_multiple_of(64) && self.shared.start.elapsed() >= movetime,
+            None => false,
+        };
+
+        if out_of_nodes || out_of_time {
+            self.shared.stop.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+}
+
+fn is_capture(game: &Game, m: &Move) -> bool {
+    // in Chess960 castling moves the king onto its own rook
+    if let Some(piece) = game.live_pieces.get(&m.to) {
+        return piece.color != game.turn;
+    }
+    // en passant, a pawn that moves diagonally to an empty square
+    match game.live_pieces.get(&m.from) {
+        Some(piece) => piece.piece_type == PieceType::Pawn && m.from.x != m.to.x,
+        None => false,
+    }
+}
+
+// rough piece values used for move ordering
+fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::King => 0,
+        PieceType::Queen => 9,
+        PieceType::Rook => 5,
+        PieceType::Bishop => 3,
+        PieceType::Knight => 3,
+        PieceType::Pawn => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::Variant;
+    use crate::{Rank, Square};
+
+    #[test]
+    fn test_mate_in_one() {
+        let engine = Engine::new();
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let result = engine.search(&game, &SearchLimits::depth(3));
+        assert_eq!(result.best_move, Some(Move::new(Square::A1, Square::A8)));
+        assert_eq!(mate_in(result.score), Some(1));
+    }
+
+    #[test]
+    fn test_antichess_win() {
+        // black has to take the rook on a8 or h1 and then white has no pieces left
+        let engine = Engine::new();
+        let game = Game::from_fen_variant("7r/8/8/8/8/8/8/R7 w - - 0 1", Variant::Antichess);
+        let result = engine.search(&game, &SearchLimits::depth(3));
+        let best_move = result.best_move.unwrap();
+        assert!(best_move == Move::new(Square::A1, Square::A8) || best_move == Move::new(Square::A1, Square::H1));
+        assert_eq!(mate_in(result.score), Some(1));
+    }
+
+    #[test]
+    fn test_racing_kings_win() {
+        // the black king can not reach the eighth rank after white gets there, the game is won after black's move
+        let engine = Engine::new();
+        let game = Game::from_fen_variant("8/1K6/6k1/8/8/8/8/8 w - - 0 1", Variant::RacingKings);
+        let result = engine.search(&game, &SearchLimits::depth(3));
+        assert_eq!(result.best_move.unwrap().to.rank(), Rank::Eighth);
+        assert_eq!(mate_in(result.score), Some(1));
+
+        // here black can equalise and it is a draw
+        let game = Game::from_fen_variant("8/1K4k1/8/8/8/8/8/8 w - - 0 1", Variant::RacingKings);
+        let result = engine.search(&game, &SearchLimits::depth(3));
+        assert_eq!(mate_in(result.score), None);
+    }
+
+    #[test]
+    fn test_wins_material() {
+        // the black queen can be taken for free
+        let engine = Engine::new();
+        let game = Game::from_fen("rnb1kbnr/pppp1ppp/8/4p1q1/4P3/3P4/PPP2PPP/RNBQKBNR w KQkq - 1 3");
+        let result = engine.search(&game, &SearchLimits::depth(2));
+        assert_eq!(result.best_move, Some(Move::new(Square::C1, Square::G5)));
+        assert!(result.score > 500);
+    }
+
+    #[test]
+    fn test_deterministic_and_threads() {
+        let game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+
+        // a single thread gives the same result every time
+        let engine1 = Engine::new();
+        let engine2 = Engine::new();
+        let result1 = engine1.search(&game, &SearchLimits::depth(3));
+        let result2 = engine2.search(&game, &SearchLimits::depth(3));
+        assert_eq!(result1.best_move, result2.best_move);
+        assert_eq!(result1.score, result2.score);
+        assert_eq!(result1.nodes, result2.nodes);
+
+        // several threads still give a legal move
+        let mut engine = Engine::new();
+        assert!(engine.set_option("Threads", "4").is_ok());
+        assert!(engine.set_option("Threads", "0").is_err());
+        assert!(engine.set_option("Hash", "1").is_ok());
+        let result = engine.search(&game, &SearchLimits::depth(3));
+        assert!(game.get_all_moves().contains(&result.best_move.unwrap()));
+        assert_eq!(result.depth, 3);
+    }
+
+    #[test]
+    fn test_strength_options() {
+        let game = Game::from_fen("rnb1kbnr/pppp1ppp/8/4p1q1/4P3/3P4/PPP2PPP/RNBQKBNR w KQkq - 1 3");
+        let best = Some(Move::new(Square::C1, Square::G5));
+
+        let mut engine = Engine::new();
+        assert!(engine.strength().is_none());
+        assert!(engine.set_option("Personality", "Materialistic").is_ok());
+        assert!(engine.set_option("Personality", "Sneaky").is_err());
+        assert_eq!(engine.search(&game, &SearchLimits::depth(2)).best_move, best);
+
+        // the weakest level misses free material now and then, but is repeatable with the same seed
+        assert!(engine.set_option("UCI_LimitStrength", "true").is_ok());
+        assert!(engine.set_option("UCI_Elo", "400").is_ok());
+        assert!(engine.set_option("UCI_Elo", "9000").is_err());
+        assert_eq!(engine.strength(), Some(Strength::from_elo(400)));
+        let mut missed = 0;
+        for seed in 0..40 {
+            engine.set_option("Seed", &seed.to_string()).unwrap();
+            let result = engine.search(&game, &SearchLimits::default());
+            assert!(result.depth <= 1);
+            engine.new_game();
+            assert_eq!(engine.search(&game, &SearchLimits::default()).best_move, result.best_move);
+            if result.best_move != best {
+                // the score is for the move that was played and not for taking the queen
+                assert!(result.score < 500);
+                missed += 1;
+            }
+        }
+        assert!(missed > 0 && missed < 40);
+
+        // skill level 20 is full strength
+        assert!(engine.set_option("UCI_LimitStrength", "false").is_ok());
+        assert!(engine.set_option("Skill Level", "20").is_ok());
+        assert!(engine.strength().is_none());
+    }
+
+    #[test]
+    fn test_no_moves() {
+        let engine = Engine::new();
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        let result = engine.search(&game, &SearchLimits::depth(2));
+        assert!(result.best_move.is_none());
+    }
+
+    #[test]
+    fn test_stopped_search() {
+        // the search is stopped in the middle of an iteration, the root of it is not stored
+        let engine = Engine::new();
+        let game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let result = engine.search(&game, &SearchLimits { nodes: Some(3000), depth: Some(10), ..Default::default() });
+        assert!(result.depth < 10 && result.nodes >= 3000);
+        assert!(game.get_all_moves().contains(&result.best_move.unwrap()));
+        let entry = engine.tt.probe(game.hash(), 0).unwrap();
+        assert_eq!(entry.depth as u32, result.depth);
+    }
+}
@@ -0,0 +1,153 @@
+// serde support, turned on with the "serde" feature.
+//
+// Squares are written as "e4" and moves in UCI notation so the output stays small and readable.
+// A Game is written as the position it started from and the moves played since, instead of its internal state.
+// Reading a game replays the moves, so the repetition history and captured pieces come back as well:
+//  {"variant":"Standard","chess960":false,"start_fen":"rnbqkbnr/...","moves":["e2e4","e7e5"],"fen":"rnbqkbnr/...",
+//   "result":"Ongoing","termination":null}
+// The current FEN is included for convenience (such as searching stored games) and is checked when reading.
+// Clocks are not written since Game does not keep the players' times, a UI that runs the clocks ends the game with
+// flag() and that is kept in the result and the termination.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::variant::Variant;
+use crate::{ChessResult, Game, Move, Square, Termination};
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_notation().to_ascii_lowercase())
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = String::deserialize(deserializer)?;
+        Square::from_notation(&notation).ok_or(D::Error::custom(format!("invalid square \"{}\"", notation)))
+    }
+}
+
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_uci())
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uci = String::deserialize(deserializer)?;
+        Move::from_uci(&uci).ok_or_else(|| D::Error::custom(format!("invalid move \"{}\"", uci)))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGame {
+    variant: Variant,
+    // the castling moves are written as the king taking its own rook, the start FEN does not always tell
+    #[serde(default)]
+    chess960: bool,
+    start_fen: String,
+    moves: Vec<Move>,
+    fen: String,
+    // games can be ended by declare_draw(), declare_win() or flag() which the moves do not show
+    result: ChessResult,
+    #[serde(default)]
+    termination: Option<Termination>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedGame {
+            variant: self.variant,
+            chess960: self.chess960,
+            start_fen: self.start_fen.clone(),
+            moves: self.history.clone(),
+            fen: self.to_fen(),
+            result: self.result,
+            termination: self.termination,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedGame::deserialize(deserializer)?;
+
+        let mut game = Game::from_fen_variant(&serialized.start_fen, serialized.variant);
+        game.chess960 |= serialized.chess960;
+        for (i, m) in serialized.moves.iter().enumerate() {
+            // the last move may still be waiting for the promotion
+            let pending_promotion = i + 1 == serialized.moves.len() && m.promotion.is_none() && m.drop.is_none()
+                && game.try_move(&m.from, &m.to).1;
+            let done = if pending_promotion { game.do_move(&m.from, &m.to) } else { game.make_move(m) };
+            if !done {
+                return Err(D::Error::custom(format!("move {} is not legal", m.to_uci())));
+            }
+        }
+
+        if game.to_fen() != serialized.fen {
+            return Err(D::Error::custom(format!("the moves lead to \"{}\" and not \"{}\"", game.to_fen(), serialized.fen)));
+        }
+        if game.result == ChessResult::Ongoing && serialized.result != ChessResult::Ongoing {
+            game.result = serialized.result;
+            game.termination = Some(serialized.termination.unwrap_or(Termination::Declared));
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Piece, PieceColor, PieceType};
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(serde_json::to_string(&Square::E4).unwrap(), "\"e4\"");
+        assert!(serde_json::from_str::<Square>("\"h8\"").unwrap() == Square::H8);
+        assert!(serde_json::from_str::<Square>("\"z9\"").is_err());
+        assert_eq!(serde_json::to_string(&PieceColor::Black).unwrap(), "\"Black\"");
+
+        let piece = Piece { piece_type: PieceType::Knight, color: PieceColor::White, pos: Square::G1, has_moved: false };
+        assert_eq!(serde_json::to_string(&piece).unwrap(), r#"{"piece_type":"Knight","color":"White","pos":"g1","has_moved":false}"#);
+
+        // replaying restores everything, including the repetition history
+        let mut game = Game::new();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let json = serde_json::to_string(&game).unwrap();
+        let mut copy: Game = serde_json::from_str(&json).unwrap();
+        assert!(copy.to_fen() == game.to_fen() && copy.history().len() == 7);
+        assert!(copy.make_move(&Move::from_uci("f6g8").unwrap()));
+        assert_eq!(copy.result, ChessResult::Draw);
+
+        // a promotion that is not chosen yet and a declared result
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(game.do_move(&Square::B7, &Square::B8));
+        let copy: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert!(copy.promotion);
+        let mut game = Game::new();
+        game.declare_win(PieceColor::Black);
+        let copy: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(copy.result, ChessResult::BlackWon);
+
+        let mut game = Game::new();
+        game.flag(PieceColor::White);
+        let copy: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert!(copy.result == ChessResult::BlackWon && copy.termination() == Some(Termination::TimeForfeit));
+
+        // Chess960 castling is the king taking its own rook, even when the pieces start on the usual squares
+        let mut game = Game::new_chess960(518).unwrap();
+        for uci in ["e2e4", "e7e5", "g1f3", "g8f6", "f1e2", "f8e7", "e1h1"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let copy: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert!(copy.chess960 && copy.to_fen() == game.to_fen());
+
+        let broken = json.replace("f3g1", "f3g5");
+        assert!(serde_json::from_str::<Game>(&broken).is_err());
+    }
+}
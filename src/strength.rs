@@ -0,0 +1,206 @@
+// Playing strength and personalities for Engine.
+//
+// A weaker engine is made by combining three things:
+//  - a cap on the search depth and on the number of nodes
+//  - random noise added to the evaluation, so small differences between moves are not seen
+//  - blunders, now and then a random move is played instead of the best one
+// The numbers are calibrated roughly to rating bands, see ELO_TABLE.
+//
+// Personalities change the evaluation weights so the engine prefers a style of play.
+
+use crate::eval::EvalParams;
+
+pub const MIN_ELO: u32 = 400;
+pub const MAX_ELO: u32 = 2400;
+pub const MAX_SKILL_LEVEL: u32 = 20;
+
+// elo, depth, nodes, eval noise in centipawns, blunder chance in permille
+const ELO_TABLE: [(u32, u32, u64, i32, u32); 6] = [
+    (400, 1, 200, 200, 250),
+    (800, 1, 1000, 120, 150),
+    (1200, 2, 5000, 70, 80),
+    (1600, 3, 20000, 40, 40),
+    (2000, 4, 80000, 20, 15),
+    (2400, 5, 300000, 0, 0),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Strength {
+    // maximum search depth
+    pub depth: u32,
+    // maximum number of nodes per search
+    pub nodes: u64,
+    // the evaluation is changed by up to this many centipawns in either direction
+    pub eval_noise: i32,
+    // chance in permille to play a random move instead of the best one
+    pub blunder_chance: u32,
+}
+
+impl Strength {
+    // returns the strength for a rating between MIN_ELO and MAX_ELO, values in between the table are interpolated
+    pub fn from_elo(elo: u32) -> Self {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+
+        let mut i = 0;
+        while i + 2 < ELO_TABLE.len() && ELO_TABLE[i + 1].0 <= elo {
+            i += 1;
+        }
+        let (low_elo, low_depth, low_nodes, low_noise, low_blunder) = ELO_TABLE[i];
+        let (high_elo, high_depth, high_nodes, high_noise, high_blunder) = ELO_TABLE[i + 1];
+
+        // how far between the two rows, 0-1000
+        let t = ((elo - low_elo) * 1000 / (high_elo - low_elo)) as i64;
+        let lerp = |low: i64, high: i64| low + (high - low) * t / 1000;
+
+        Self {
+            depth: lerp(low_depth as i64, high_depth as i64) as u32,
+            nodes: lerp(low_nodes as i64, high_nodes as i64) as u64,
+            eval_noise: lerp(low_noise as i64, high_noise as i64) as i32,
+            blunder_chance: lerp(low_blunder as i64, high_blunder as i64) as u32,
+        }
+    }
+
+    // returns the strength for a skill level between 0 and MAX_SKILL_LEVEL, spread evenly over the ratings
+    pub fn from_skill_level(level: u32) -> Self {
+        let level = level.min(MAX_SKILL_LEVEL);
+        Self::from_elo(MIN_ELO + (MAX_ELO - MIN_ELO) * level / MAX_SKILL_LEVEL)
+    }
+
+    // returns the noise for a position, it is always the same for the same position and seed
+    pub(crate) fn noise(&self, hash: u64, seed: u64) -> i32 {
+        if self.eval_noise == 0 {
+            return 0;
+        }
+        let range = 2 * self.eval_noise as u64 + 1;
+        (random(hash ^ seed) % range) as i32 - self.eval_noise
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Personality {
+    // the default evaluation
+    Balanced,
+    // attacks the king, values activity and avoids draws
+    Aggressive,
+    // values good pawn structure, piece placement and the bishop pair
+    Positional,
+    // grabs material whenever it can
+    Materialistic,
+}
+
+impl Personality {
+    // returns the personality with the given name (case insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "balanced" | "default" => Some(Personality::Balanced),
+            "aggressive" => Some(Personality::Aggressive),
+            "positional" => Some(Personality::Positional),
+            "materialistic" => Some(Personality::Materialistic),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Personality::Balanced => "Balanced",
+            Personality::Aggressive => "Aggressive",
+            Personality::Positional => "Positional",
+            Personality::Materialistic => "Materialistic",
+        }
+    }
+
+    // returns a copy of params with the weights of this personality
+    pub fn apply(&self, params: &EvalParams) -> EvalParams {
+        let mut params = params.clone();
+        match self {
+            Personality::Balanced => (),
+            Personality::Aggressive => {
+                params.king_attack_mg *= 3;
+                scale(&mut params.mobility_mg, 3, 2);
+                scale(&mut params.mobility_eg, 3, 2);
+                scale(&mut params.passed_pawn_mg, 3, 2);
+            },
+            Personality::Positional => {
+                for table in params.pst_mg.iter_mut().chain(params.pst_eg.iter_mut()) {
+                    scale(table, 3, 2);
+                }
+                params.doubled_pawn_mg *= 2;
+                params.doubled_pawn_eg *= 2;
+                params.isolated_pawn_mg *= 2;
+                params.isolated_pawn_eg *= 2;
+                params.bishop_pair_mg *= 2;
+                params.bishop_pair_eg *= 2;
+                params.king_shield_mg *= 2;
+            },
+            Personality::Materialistic => {
+                scale(&mut params.material_mg, 6, 5);
+                scale(&mut params.material_eg, 6, 5);
+                scale(&mut params.mobility_mg, 1, 2);
+                scale(&mut params.mobility_eg, 1, 2);
+                params.king_attack_mg /= 2;
+            },
+        }
+        params
+    }
+
+    // score of a draw for the side to move in centipawns, a negative value makes the engine avoid draws
+    pub fn draw_score(&self) -> i32 {
+        match self {
+            Personality::Aggressive => -30,
+            _ => 0,
+        }
+    }
+}
+
+fn scale(values: &mut [i32], numerator: i32, denominator: i32) {
+    for value in values {
+        *value = *value * numerator / denominator;
+    }
+}
+
+// splitmix64, a fast random number from a seed
+pub(crate) fn random(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strength() {
+        // weaker ratings search less and blunder more
+        let mut previous = Strength::from_elo(0);
+        assert_eq!(previous, Strength::from_elo(MIN_ELO));
+        for elo in (MIN_ELO..=MAX_ELO).step_by(100) {
+            let strength = Strength::from_elo(elo);
+            assert!(strength.depth >= previous.depth);
+            assert!(strength.nodes >= previous.nodes);
+            assert!(strength.eval_noise <= previous.eval_noise);
+            assert!(strength.blunder_chance <= previous.blunder_chance);
+            previous = strength;
+        }
+        assert_eq!(Strength::from_elo(MAX_ELO).blunder_chance, 0);
+        assert_eq!(Strength::from_skill_level(MAX_SKILL_LEVEL), Strength::from_elo(MAX_ELO));
+
+        // noise stays within the range and depends on the seed
+        let strength = Strength::from_elo(800);
+        let noises: Vec<i32> = (0..100).map(|seed| strength.noise(12345, seed)).collect();
+        assert!(noises.iter().all(|n| n.abs() <= strength.eval_noise));
+        assert!(noises.iter().any(|n| *n != noises[0]));
+    }
+
+    #[test]
+    fn test_personality() {
+        let params = EvalParams::default();
+        assert_eq!(Personality::Balanced.apply(&params), params);
+        assert!(Personality::Aggressive.apply(&params).king_attack_mg < params.king_attack_mg);
+        assert!(Personality::Materialistic.apply(&params).material_mg[1] > params.material_mg[1]);
+        assert!(Personality::Positional.apply(&params).bishop_pair_mg > params.bishop_pair_mg);
+        assert_eq!(Personality::from_name("AGGRESSIVE"), Some(Personality::Aggressive));
+        assert!(Personality::from_name("sneaky").is_none());
+    }
+}
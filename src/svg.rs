@@ -0,0 +1,224 @@
+// SVG diagrams of a position, for worksheets and web pages.
+//
+// The pieces are simple shapes drawn in the file itself so the image does not need fonts or other files. A square is
+// 45 units wide and the board is 360 units, the image is scaled to SvgOptions::size pixels.
+
+use crate::{Game, PieceColor, PieceType, Square};
+
+const SQUARE: f64 = 45.0;
+
+// the shape of each piece in a 45x45 box, drawn with the fill and outline of its color
+const PAWN: &str = r#"<circle cx="22.5" cy="14" r="5.5"/><path d="M17 21 h11 l3 11 h-17 z"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+const KNIGHT: &str = r#"<path d="M14 38 l2-10 c-4-2-6-6-3-10 l8-8 l1-4 l3 4 c7 2 10 9 9 18 l-1 10 z"/><circle cx="19" cy="15" r="1.5" class="eye"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+const BISHOP: &str = r#"<circle cx="22.5" cy="8" r="3"/><path d="M22.5 11 c-7 5-9 11-5 17 h10 c4-6 2-12-5-17 z"/><path d="M20 17 l5 5" class="line"/><path d="M16 28 h13 l2 4 h-17 z"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+const ROOK: &str = r#"<path d="M12 8 h4 v4 h4 v-4 h5 v4 h4 v-4 h4 v8 l-3 3 h-15 l-3-3 z"/><path d="M15 19 h15 l1 13 h-17 z"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+const QUEEN: &str = r#"<path d="M10 14 l5 14 h15 l5-14 l-7 8 l-5.5-12 l-5.5 12 z"/><circle cx="10" cy="12" r="2.5"/><circle cx="22.5" cy="8" r="2.5"/><circle cx="35" cy="12" r="2.5"/><path d="M15 28 h15 l1 4 h-17 z"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+const KING: &str = r#"<path d="M21 4 h3 v4 h4 v3 h-4 v4 h-3 v-4 h-4 v-3 h4 z"/><path d="M22.5 15 c-10 0-14 6-8 14 h16 c6-8 2-14-8-14 z"/><path d="M15 29 h15 l1 3 h-17 z"/><rect x="11" y="32" width="23" height="6" rx="2"/>"#;
+
+// an arrow from the center of one square to another, such as a planned move
+#[derive(Clone, Debug)]
+pub struct Arrow {
+    pub from: Square,
+    pub to: Square,
+    // any SVG color such as "#15781b" or "red"
+    pub color: String,
+}
+
+// a circle around a square
+#[derive(Clone, Debug)]
+pub struct Mark {
+    pub square: Square,
+    pub color: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    // width and height in pixels
+    pub size: u32,
+    // shows the board from black's side
+    pub flipped: bool,
+    pub light_color: String,
+    pub dark_color: String,
+    // the files and ranks in the corners of the edge squares
+    pub coordinates: bool,
+    // colors the squares of the last move
+    pub last_move: bool,
+    pub last_move_color: String,
+    // colors the square of a king in check
+    pub check: bool,
+    pub check_color: String,
+    pub arrows: Vec<Arrow>,
+    pub marks: Vec<Mark>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            size: 360,
+            flipped: false,
+            light_color: "#f0d9b5".to_string(),
+            dark_color: "#b58863".to_string(),
+            coordinates: true,
+            last_move: true,
+            last_move_color: "#cdd26a".to_string(),
+            check: true,
+            check_color: "#e8413a".to_string(),
+            arrows: Vec::new(),
+            marks: Vec::new(),
+        }
+    }
+}
+
+impl Game {
+    // returns an SVG image of the position
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let board = SQUARE * 8.0;
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {board} {board}">"#,
+            size = options.size, board = board);
+
+        svg.push_str("<style>.white{fill:#fff;stroke:#000;stroke-width:1.5;stroke-linejoin:round}.black{fill:#000;stroke:#000;stroke-width:1.5;stroke-linejoin:round}");
+        svg.push_str(".white .eye{fill:#000}.black .eye{fill:#fff;stroke:none}.line{fill:none}.black .line{stroke:#fff}");
+        svg.push_str(".coordinate{font-family:sans-serif;font-size:9px;font-weight:bold}</style>");
+
+        // the squares
+        for y in 0..8 {
+            for x in 0..8 {
+                let square = Square::at(x, y);
+                let (left, top) = corner(square, options.flipped);
+                let color = if (x + y) % 2 == 0 { &options.dark_color } else { &options.light_color };
+                svg.push_str(&format!(r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, left, top, SQUARE, SQUARE, color));
+            }
+        }
+
+        if options.last_move && !self.history().is_empty() {
+            for square in [self.last_moved_from, self.last_moved_to].into_iter().flatten() {
+                let (left, top) = corner(square, options.flipped);
+                svg.push_str(&format!(r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" opacity="0.6"/>"#,
+                    left, top, SQUARE, SQUARE, options.last_move_color));
+            }
+        }
+
+        if options.check && self.check {
+            if let Some(king) = self.get_board_state().values().find(|p| p.piece_type == PieceType::King && p.color == self.turn) {
+                let (left, top) = corner(king.pos, options.flipped);
+                svg.push_str(&format!(r#"<circle cx="{}" cy="{}" r="{}" fill="{}" opacity="0.8"/>"#,
+                    left + SQUARE / 2.0, top + SQUARE / 2.0, SQUARE / 2.0, options.check_color));
+            }
+        }
+
+        if options.coordinates {
+            // the ranks in the left column and the files in the bottom row, in the color of the other squares
+            let text_color = |square: Square| if (square.x + square.y) % 2 == 0 { &options.light_color } else { &options.dark_color };
+            for i in 0..8 {
+                let left_square = Square::at(if options.flipped { 7 } else { 0 }, i);
+                let (_, top) = corner(left_square, options.flipped);
+                svg.push_str(&format!(r#"<text x="2" y="{}" fill="{}" class="coordinate">{}</text>"#, top + 10.0, text_color(left_square), i + 1));
+
+                let bottom_square = Square::at(i, if options.flipped { 7 } else { 0 });
+                let (left, _) = corner(bottom_square, options.flipped);
+                svg.push_str(&format!(r#"<text x="{}" y="{}" fill="{}" class="coordinate" text-anchor="end">{}</text>"#,
+                    left + SQUARE - 2.0, board - 3.0, text_color(bottom_square), (b'a' + i as u8) as char));
+            }
+        }
+
+        // the pieces, in a fixed order so the same position always gives the same image
+        let mut pieces: Vec<_> = self.get_board_state().values().collect();
+        pieces.sort_by_key(|piece| (piece.pos.y, piece.pos.x));
+        for piece in pieces {
+            let (left, top) = corner(piece.pos, options.flipped);
+            let shape = match piece.piece_type {
+                PieceType::Pawn => PAWN,
+                PieceType::Knight => KNIGHT,
+                PieceType::Bishop => BISHOP,
+                PieceType::Rook => ROOK,
+                PieceType::Queen => QUEEN,
+                PieceType::King => KING,
+            };
+            let class = match piece.color {
+                PieceColor::White => "white",
+                PieceColor::Black => "black",
+            };
+            svg.push_str(&format!(r#"<g class="{}" transform="translate({},{})">{}</g>"#, class, left, top, shape));
+        }
+
+        for mark in &options.marks {
+            let (left, top) = corner(mark.square, options.flipped);
+            svg.push_str(&format!(r#"<circle cx="{}" cy="{}" r="20" fill="none" stroke="{}" stroke-width="3" opacity="0.8"/>"#,
+                left + SQUARE / 2.0, top + SQUARE / 2.0, mark.color));
+        }
+
+        for arrow in &options.arrows {
+            svg.push_str(&arrow_svg(arrow, options.flipped));
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+// returns the top left corner of the square in the image
+fn corner(square: Square, flipped: bool) -> (f64, f64) {
+    let (x, y) = if flipped { (7 - square.x, square.y) } else { (square.x, 7 - square.y) };
+    (x as f64 * SQUARE, y as f64 * SQUARE)
+}
+
+// a line with a triangle at the end, the line stops at the triangle so they do not overlap when transparent
+fn arrow_svg(arrow: &Arrow, flipped: bool) -> String {
+    let center = |square: Square| {
+        let (left, top) = corner(square, flipped);
+        (left + SQUARE / 2.0, top + SQUARE / 2.0)
+    };
+    let (x1, y1) = center(arrow.from);
+    let (x2, y2) = center(arrow.to);
+    let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+    if length == 0.0 {
+        return String::new();
+    }
+
+    let (dx, dy) = ((x2 - x1) / length, (y2 - y1) / length);
+    let head = 18.0;
+    let (bx, by) = (x2 - dx * head, y2 - dy * head);
+    let (px, py) = (-dy * 11.0, dx * 11.0);
+
+    format!(
+        r#"<g opacity="0.8"><line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{color}" stroke-width="8"/><polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="{color}"/></g>"#,
+        x1, y1, bx, by, x2, y2, bx + px, by + py, bx - px, by - py, color = arrow.color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn test_svg() {
+        let mut game = Game::new();
+        let svg = game.to_svg(&SvgOptions::default());
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert!(svg.matches(r#"<g class="white""#).count() == 16 && svg.matches(r#"<g class="black""#).count() == 16);
+        // no move has been played yet
+        assert!(!svg.contains("#cdd26a"));
+
+        for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let options = SvgOptions {
+            size: 400,
+            flipped: true,
+            arrows: vec![Arrow { from: Square::H4, to: Square::E1, color: "red".to_string() }],
+            marks: vec![Mark { square: Square::E1, color: "blue".to_string() }],
+            ..SvgOptions::default()
+        };
+        let svg = game.to_svg(&options);
+        assert!(svg.contains(r#"width="400""#));
+        assert!(svg.matches("#cdd26a").count() == 2 && svg.contains("#e8413a"));
+        assert!(svg.contains(r#"fill="red""#) && svg.contains(r#"stroke="blue""#));
+        // e1 is in the top row when flipped
+        assert!(svg.contains(r#"<g class="white" transform="translate(135,0)">"#));
+        assert!(svg.contains(r#"<circle cx="157.5" cy="22.5" r="22.5""#));
+
+        let svg = game.to_svg(&SvgOptions { coordinates: false, check: false, last_move: false, ..SvgOptions::default() });
+        assert!(!svg.contains("coordinate\">") && !svg.contains("#e8413a") && !svg.contains("#cdd26a"));
+    }
+}
@@ -0,0 +1,1331 @@
+// Probing of Syzygy endgame tablebases from local files.
+//
+// Syzygy tables come in two kinds, WDL (.rtbw) tells if a position is won, drawn or lost and DTZ (.rtbz) tells the
+// distance to zeroing, the number of half-moves until the next capture or pawn move in a perfect game. Together they
+// are enough to play an endgame perfectly while respecting the fifty-move rule.
+//
+// The files are read from a directory the first time a table is needed and kept in memory. A file is read whole
+// and stays loaded for as long as the Tablebase lives, nothing is evicted, so the memory used is the size of all the
+// files that have been probed. That is fine for the 3-5 piece tables (under 1 GB in total) but the 6 and 7 piece
+// files are much larger, only point a Tablebase at those if there is enough memory for the tables that will be hit.
+// Positions with castling rights are not in the tables and are never probed.
+//
+// The tables are compressed and indexed with a scheme that is explained in the comments below, it follows the
+// layout used by the generator so every detail (the order of the pieces, the mirroring of squares and so on)
+// matters. Values for positions where the best move is a capture are not always stored correctly in the tables,
+// which is why probing does a small search over captures first.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::variant::Variant;
+use crate::{ChessResult, Game, Move, PieceColor, PieceType, Square};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+// the most pieces (kings included) a table can have
+const TB_PIECES: usize = 7;
+const MAX_DTZ: i32 = 1 << 18;
+
+// flags of a table
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+// win/draw/loss from the point of view of the side to move
+// a cursed win is a win that is a draw because of the fifty-move rule, a blessed loss is the opposite
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_i32(value: i32) -> Self {
+        match value {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+}
+
+// the result of probing a position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TbResult {
+    // the result when the fifty-move counter of the game is taken into account
+    pub wdl: Wdl,
+    // half-moves to the next capture or pawn move with perfect play, positive if winning, negative if losing, 0 if drawn
+    pub dtz: i32,
+}
+
+pub struct Tablebase {
+    directory: PathBuf,
+    max_pieces: usize,
+    // tables that have been read, None if the file is missing or broken
+    wdl_tables: Mutex<HashMap<String, Option<Arc<Table>>>>,
+    dtz_tables: Mutex<HashMap<String, Option<Arc<Table>>>>,
+}
+
+impl fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tablebase").field("directory", &self.directory).field("max_pieces", &self.max_pieces).finish()
+    }
+}
+
+impl Tablebase {
+    // opens a directory with Syzygy files, the files themselves are read when they are needed
+    pub fn open(directory: &str) -> Result<Self, String> {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(e) => return Err(format!("could not read {}: {}", directory, e)),
+        };
+
+        // the largest table decides how many pieces can be probed
+        let mut max_pieces = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(code) = name.strip_suffix(".rtbw") {
+                if code.contains('v') && code.chars().all(|c| "KQRBNPv".contains(c)) {
+                    max_pieces = max_pieces.max(code.len() - 1);
+                }
+            }
+        }
+
+        Ok(Self {
+            directory: PathBuf::from(directory),
+            max_pieces: max_pieces.min(TB_PIECES),
+            wdl_tables: Mutex::new(HashMap::new()),
+            dtz_tables: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // the most pieces (kings included) that the files in the directory cover
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // returns the result of the position for the side to move and the distance to zeroing
+    // the fifty-move counter of the game is used to tell wins from cursed wins and losses from blessed losses
+    pub fn probe(&self, game: &Game) -> Option<TbResult> {
+        if !self.can_probe(game) {
+            return None;
+        }
+
+        let wdl = self.probe_wdl(game)?;
+        let dtz = self.probe_dtz(game)?;
+
+        let wdl = match wdl {
+            Wdl::Win if dtz + game.fifty_move_rule as i32 > 100 => Wdl::CursedWin,
+            Wdl::Loss if -dtz + game.fifty_move_rule as i32 > 100 => Wdl::BlessedLoss,
+            _ => wdl,
+        };
+        Some(TbResult { wdl, dtz })
+    }
+
+    // returns the result stored in the tables for the side to move, without looking at the fifty-move counter
+    pub fn probe_wdl(&self, game: &Game) -> Option<Wdl> {
+        if !self.can_probe(game) {
+            return None;
+        }
+
+        let mut state = ProbeState::Ok;
+        let wdl = self.search(&normalized(game), &mut state, false);
+        if state == ProbeState::Fail {
+            return None;
+        }
+        Some(Wdl::from_i32(wdl))
+    }
+
+    // returns the distance to zeroing in half-moves, positive if the side to move wins, negative if it loses
+    // and 0 if it is a draw. A position where the side to move is mated has dtz -1
+    pub fn probe_dtz(&self, game: &Game) -> Option<i32> {
+        if !self.can_probe(game) {
+            return None;
+        }
+
+        let mut state = ProbeState::Ok;
+        let dtz = self.dtz(&normalized(game), &mut state);
+        if state == ProbeState::Fail {
+            return None;
+        }
+        Some(dtz)
+    }
+
+    // returns the best move according to the tables and the result after playing it
+    // wins that can be reached before the fifty-move rule kicks in are preferred, then the quickest win,
+    // when losing the move that resists the longest is picked
+    pub fn best_move(&self, game: &Game) -> Option<(Move, TbResult)> {
+        if !self.can_probe(game) {
+            return None;
+        }
+
+        let fifty = game.fifty_move_rule as i32;
+        let mut best: Option<(i64, Move, i32)> = None;
+
+        for m in game.get_all_moves() {
+            let mut child = game.clone();
+            child.make_move(&m);
+
+            let mut state = ProbeState::Ok;
+            let dtz = match child.result {
+                // mate
+                ChessResult::WhiteWon | ChessResult::BlackWon => 1,
+                _ if child.fifty_move_rule == 0 => {
+                    // a zeroing move, the result of the move decides
+                    let wdl = -self.search(&normalized(&child), &mut state, false);
+                    dtz_before_zeroing(wdl)
+                },
+                // the move draws by repetition or the fifty-move rule
+                ChessResult::Draw => 0,
+                _ => {
+                    let dtz = -self.dtz(&normalized(&child), &mut state);
+                    dtz + dtz.signum()
+                },
+            };
+            if state == ProbeState::Fail {
+                return None;
+            }
+
+            // higher is better
+            let rank = if dtz > 0 {
+                if dtz + fifty <= 100 { 3 * MAX_DTZ as i64 - dtz as i64 } else { 2 * MAX_DTZ as i64 - dtz as i64 }
+            } else if dtz < 0 {
+                if -dtz + fifty <= 100 { -3 * MAX_DTZ as i64 - dtz as i64 } else { -2 * MAX_DTZ as i64 - dtz as i64 }
+            } else {
+                0
+            };
+
+            if best.is_none_or(|(best_rank, _, _)| rank > best_rank) {
+                best = Some((rank, m, dtz));
+            }
+        }
+
+        let (_, m, dtz) = best?;
+        let wdl = if dtz > 0 {
+            if dtz + fifty <= 100 { Wdl::Win } else { Wdl::CursedWin }
+        } else if dtz < 0 {
+            if -dtz + fifty <= 100 { Wdl::Loss } else { Wdl::BlessedLoss }
+        } else {
+            Wdl::Draw
+        };
+        Some((m, TbResult { wdl, dtz }))
+    }
+
+    fn can_probe(&self, game: &Game) -> bool {
+        game.variant() == Variant::Standard &&
+            game.live_pieces.len() <= self.max_pieces.max(2) &&
+            !game.promotion &&
+            !game.castling_rights().iter().any(|right| *right)
+    }
+
+    // searches captures (and pawn moves if check_zeroing) because the tables may store wrong values for positions
+    // where such a move is the best, returns the wdl value -2 to 2
+    fn search(&self, game: &Game, state: &mut ProbeState, check_zeroing: bool) -> i32 {
+        let moves = game.get_all_moves();
+        if moves.is_empty() {
+            // mate or stalemate
+            *state = ProbeState::Ok;
+            return if game.check { -2 } else { 0 };
+        }
+
+        let mut best_value = -2;
+        let mut move_count = 0;
+
+        for m in &moves {
+            let is_pawn = matches!(game.live_pieces.get(&m.from), Some(p) if p.piece_type == PieceType::Pawn);
+            if !(is_capture(game, m) || check_zeroing && is_pawn) {
+                continue;
+            }
+
+            move_count += 1;
+            let mut child = game.clone();
+            child.make_move(m);
+            let value = -self.search(&normalized(&child), state, false);
+
+            if *state == ProbeState::Fail {
+                return 0;
+            }
+
+            if value > best_value {
+                best_value = value;
+                if value >= 2 {
+                    // winning zeroing move
+                    *state = ProbeState::ZeroingBestMove;
+                    return value;
+                }
+            }
+        }
+
+        // if all moves were searched the stored value is not needed (and could be wrong)
+        let no_more_moves = move_count > 0 && move_count == moves.len();
+        let value = if no_more_moves {
+            best_value
+        } else {
+            let value = self.probe_table(game, state, false, 0);
+            if *state == ProbeState::Fail {
+                return 0;
+            }
+            value
+        };
+
+        if best_value >= value {
+            *state = if best_value > 0 || no_more_moves { ProbeState::ZeroingBestMove } else { ProbeState::Ok };
+            return best_value;
+        }
+
+        *state = ProbeState::Ok;
+        value
+    }
+
+    fn dtz(&self, game: &Game, state: &mut ProbeState) -> i32 {
+        *state = ProbeState::Ok;
+        let wdl = self.search(game, state, true);
+
+        // the tables do not store draws
+        if *state == ProbeState::Fail || wdl == 0 {
+            return 0;
+        }
+
+        // the stored value is not valid when the best move zeroes
+        if *state == ProbeState::ZeroingBestMove {
+            return dtz_before_zeroing(wdl);
+        }
+
+        let dtz = self.probe_table(game, state, true, wdl);
+        if *state == ProbeState::Fail {
+            return 0;
+        }
+        if *state != ProbeState::ChangeStm {
+            let cursed = if wdl == -1 || wdl == 1 { 100 } else { 0 };
+            return (dtz + cursed) * wdl.signum();
+        }
+
+        // the table only stores the other side to move, do a 1 ply search and find the best winning move
+        let mut min_dtz = 0xFFFF;
+        for m in game.get_all_moves() {
+            let zeroing = is_capture(game, &m) || matches!(game.live_pieces.get(&m.from), Some(p) if p.piece_type == PieceType::Pawn);
+
+            let mut child = game.clone();
+            child.make_move(&m);
+            let child = normalized(&child);
+
+            let mut dtz = if zeroing {
+                -dtz_before_zeroing(self.search(&child, state, false))
+            } else {
+                -self.dtz(&child, state)
+            };
+
+            // the move mates
+            if dtz == 1 && child.check && child.get_all_moves().is_empty() {
+                min_dtz = 1;
+            }
+
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+
+            if *state == ProbeState::Fail {
+                return 0;
+            }
+        }
+
+        if min_dtz == 0xFFFF { -1 } else { min_dtz }
+    }
+
+    // looks the position up in a WDL or DTZ table, returns the wdl value (-2 to 2) or the dtz
+    fn probe_table(&self, game: &Game, state: &mut ProbeState, dtz: bool, wdl: i32) -> i32 {
+        // KvK
+        if game.live_pieces.len() == 2 {
+            return 0;
+        }
+
+        let (white_code, black_code) = material_codes(game);
+        let key = format!("{}v{}", white_code, black_code);
+        let table = match self.table(&white_code, &black_code, dtz) {
+            Some(table) => table,
+            None => {
+                *state = ProbeState::Fail;
+                return 0;
+            },
+        };
+
+        match table.probe(game, &key, wdl) {
+            Some((value, change_stm)) => {
+                if change_stm {
+                    *state = ProbeState::ChangeStm;
+                }
+                value
+            },
+            None => {
+                *state = ProbeState::Fail;
+                0
+            },
+        }
+    }
+
+    // returns the table for the material, reading the whole file from disk the first time and keeping it
+    fn table(&self, white_code: &str, black_code: &str, dtz: bool) -> Option<Arc<Table>> {
+        let (tables, extension) = if dtz { (&self.dtz_tables, "rtbz") } else { (&self.wdl_tables, "rtbw") };
+        let mut tables = tables.lock().unwrap_or_else(|e| e.into_inner());
+
+        // the file is named with the stronger side first, the table can be found under both names
+        let key = format!("{}v{}", white_code, black_code);
+        if let Some(table) = tables.get(&key) {
+            return table.clone();
+        }
+
+        let mut table = None;
+        for code in [format!("{}v{}", white_code, black_code), format!("{}v{}", black_code, white_code)] {
+            let path = self.directory.join(format!("{}.{}", code, extension));
+            if let Ok(bytes) = fs::read(&path) {
+                table = Table::new(&code, bytes, dtz).map(Arc::new);
+                break;
+            }
+        }
+
+        tables.insert(key.clone(), table.clone());
+        tables.insert(format!("{}v{}", black_code, white_code), table.clone());
+        table
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProbeState {
+    Ok,
+    Fail,
+    // the best move zeroes the fifty-move counter, the stored dtz can not be used
+    ZeroingBestMove,
+    // the dtz table stores the other side to move
+    ChangeStm,
+}
+
+// dtz of a position where the side to move just made a zeroing move
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+// returns a copy of the game that can be probed even if it has been declared a draw by the fifty-move rule,
+// repetition or insufficient material, the tables handle all of that themselves
+fn normalized(game: &Game) -> Game {
+    let mut game = game.clone();
+    game.fifty_move_rule = 0;
+    game.previous_states.clear();
+    if game.result == ChessResult::Draw {
+        game.result = ChessResult::Ongoing;
+        game.termination = None;
+    }
+    game
+}
+
+fn is_capture(game: &Game, m: &Move) -> bool {
+    game.live_pieces.contains_key(&m.to) ||
+        matches!(game.live_pieces.get(&m.from), Some(p) if p.piece_type == PieceType::Pawn && m.from.x != m.to.x)
+}
+
+// returns the pieces of white and black like ("KRP", "KR"), in the order the table names use
+fn material_codes(game: &Game) -> (String, String) {
+    let mut codes = (String::new(), String::new());
+    for (piece_type, letter) in [(PieceType::King, 'K'), (PieceType::Queen, 'Q'), (PieceType::Rook, 'R'), (PieceType::Bishop, 'B'), (PieceType::Knight, 'N'), (PieceType::Pawn, 'P')] {
+        for piece in game.live_pieces.values().filter(|p| p.piece_type == piece_type) {
+            match piece.color {
+                PieceColor::White => codes.0.push(letter),
+                PieceColor::Black => codes.1.push(letter),
+            }
+        }
+    }
+    codes
+}
+
+// piece codes used in the files, white is 1-6 and black is 9-14
+fn piece_code(piece_type: PieceType, color: PieceColor) -> u8 {
+    let code = match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match color {
+        PieceColor::White => code,
+        PieceColor::Black => code + 8,
+    }
+}
+
+// squares are numbered 0-63 with A1 = 0, like Square::to_index()
+fn file_of(square: usize) -> usize {
+    square & 7
+}
+
+fn rank_of(square: usize) -> usize {
+    square >> 3
+}
+
+// distance from the a1-h8 diagonal, negative below it
+fn off_a1h8(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+// lookup tables used to turn a position into an index
+struct Maps {
+    // a2-h7 to 0-47, the highest value is the leading pawn
+    map_pawns: [usize; 64],
+    // squares below the a1-h8 diagonal to 0-27
+    map_b1h1h7: [usize; 64],
+    // squares in the a1-d1-d4 triangle to 0-9, the diagonal last
+    map_a1d1d4: [usize; 64],
+    // the 462 legal placements of two kings with the first in the a1-d1-d4 triangle
+    map_kk: [[usize; 64]; 10],
+    // binomial[k][n] ways to choose k of n
+    binomial: [[u64; 64]; 6],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+fn maps() -> &'static Maps {
+    static MAPS: OnceLock<Maps> = OnceLock::new();
+    MAPS.get_or_init(|| {
+        let mut maps = Maps {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for s in 0..64 {
+            if off_a1h8(s) < 0 {
+                maps.map_b1h1h7[s] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal = Vec::new();
+        code = 0;
+        for s in 0..=27 {
+            if off_a1h8(s) < 0 && file_of(s) <= 3 {
+                maps.map_a1d1d4[s] = code;
+                code += 1;
+            } else if off_a1h8(s) == 0 && file_of(s) <= 3 {
+                diagonal.push(s);
+            }
+        }
+        for s in diagonal {
+            maps.map_a1d1d4[s] = code;
+            code += 1;
+        }
+
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for idx in 0..10 {
+            for s1 in 0..=27 {
+                if maps.map_a1d1d4[s1] != idx || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64 {
+                    let adjacent = (file_of(s1) as i32 - file_of(s2) as i32).abs() <= 1 && (rank_of(s1) as i32 - rank_of(s2) as i32).abs() <= 1;
+                    if adjacent || (off_a1h8(s1) == 0 && off_a1h8(s2) > 0) {
+                        continue;
+                    }
+                    if off_a1h8(s1) == 0 && off_a1h8(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        maps.map_kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            maps.map_kk[idx][s2] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6 {
+                if k > n {
+                    break;
+                }
+                maps.binomial[k][n] = if k > 0 { maps.binomial[k - 1][n - 1] } else { 0 } +
+                    if k < n { maps.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available_squares = 47;
+        for lead_pawns_count in 1..=5 {
+            for f in 0..4 {
+                let mut idx = 0;
+                for r in 1..=6 {
+                    let sq = r * 8 + f;
+                    if lead_pawns_count == 1 {
+                        maps.map_pawns[sq] = available_squares;
+                        available_squares = available_squares.saturating_sub(1);
+                        maps.map_pawns[sq ^ 7] = available_squares;
+                        available_squares = available_squares.saturating_sub(1);
+                    }
+                    maps.lead_pawn_idx[lead_pawns_count][sq] = idx;
+                    idx += maps.binomial[lead_pawns_count - 1][maps.map_pawns[sq]];
+                }
+                maps.lead_pawns_size[lead_pawns_count][f] = idx;
+            }
+        }
+
+        maps
+    })
+}
+
+// one sub-table, tables with pawns have one per file a-d of the leading pawn and WDL tables have one per side to move
+#[derive(Clone, Default)]
+struct PairsData {
+    flags: u8,
+    // the order of the pieces in the index
+    pieces: [u8; TB_PIECES],
+    group_len: [usize; TB_PIECES + 1],
+    group_idx: [u64; TB_PIECES + 1],
+    block_size: u64,
+    span: u64,
+    sparse_index_size: u64,
+    num_blocks: u64,
+    block_length_size: u64,
+    max_sym_len: u8,
+    min_sym_len: u8,
+    // offsets into the file
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    base64: Vec<u64>,
+    // number of values - 1 that each symbol expands to
+    symlen: Vec<u8>,
+    // dtz tables, offsets to the value maps for each wdl
+    map_idx: [usize; 4],
+}
+
+struct Table {
+    bytes: Vec<u8>,
+    dtz: bool,
+    // material with white first and with black first, the same if both sides have the same pieces
+    key: String,
+    key2: String,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    // pawns of the leading color and of the other color
+    pawn_count: [usize; 2],
+    // [side][file]
+    items: Vec<Vec<PairsData>>,
+}
+
+impl Table {
+    // parses a table, code is the material like "KRPvKR" that the file is named after
+    fn new(code: &str, bytes: Vec<u8>, dtz: bool) -> Option<Self> {
+        let magic = if dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if bytes.len() < 5 || bytes[0..4] != magic {
+            return None;
+        }
+
+        let (white, black) = code.split_once('v')?;
+        let piece_count = white.len() + black.len();
+        if piece_count > TB_PIECES || !white.starts_with('K') || !black.starts_with('K') {
+            return None;
+        }
+
+        let count = |side: &str, c: char| side.chars().filter(|x| *x == c).count();
+        let has_pawns = code.contains('P');
+        let has_unique_pieces = [white, black].iter().any(|side| "QRBNP".chars().any(|c| count(side, c) == 1));
+
+        // the leading color is the side with fewer pawns, but at least one
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count = if white_leads { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] };
+
+        let mut table = Self {
+            bytes,
+            dtz,
+            key: format!("{}v{}", white, black),
+            key2: format!("{}v{}", black, white),
+            piece_count,
+            has_pawns,
+            has_unique_pieces,
+            pawn_count,
+            items: Vec::new(),
+        };
+        table.init()?;
+        Some(table)
+    }
+
+    fn byte(&self, offset: usize) -> u8 {
+        self.bytes.get(offset).copied().unwrap_or(0)
+    }
+
+    fn u16_le(&self, offset: usize) -> u16 {
+        u16::from_le_bytes([self.byte(offset), self.byte(offset + 1)])
+    }
+
+    fn u32_le(&self, offset: usize) -> u32 {
+        u32::from_le_bytes([self.byte(offset), self.byte(offset + 1), self.byte(offset + 2), self.byte(offset + 3)])
+    }
+
+    fn u32_be(&self, offset: usize) -> u32 {
+        u32::from_be_bytes([self.byte(offset), self.byte(offset + 1), self.byte(offset + 2), self.byte(offset + 3)])
+    }
+
+    // reads the headers of all sub-tables
+    fn init(&mut self) -> Option<()> {
+        let mut data = 4;
+        let split = self.key != self.key2;
+        let flags = self.byte(data);
+        if (flags & 2 != 0) != self.has_pawns || (!self.dtz && (flags & 1 != 0) != split) {
+            return None;
+        }
+        data += 1;
+
+        let sides = if !self.dtz && split { 2 } else { 1 };
+        let files = if self.has_pawns { 4 } else { 1 };
+        // pawns on both sides
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+
+        self.items = vec![vec![PairsData::default(); files]; sides];
+
+        for f in 0..files {
+            let order = [
+                [self.byte(data) & 0xF, if pp { self.byte(data + 1) & 0xF } else { 0xF }],
+                [self.byte(data) >> 4, if pp { self.byte(data + 1) >> 4 } else { 0xF }],
+            ];
+            data += 1 + pp as usize;
+
+            for k in 0..self.piece_count {
+                for i in 0..sides {
+                    self.items[i][f].pieces[k] = if i == 0 { self.byte(data) & 0xF } else { self.byte(data) >> 4 };
+                }
+                data += 1;
+            }
+
+            for (i, side_order) in order.iter().enumerate().take(sides) {
+                let mut d = self.items[i][f].clone();
+                self.set_groups(&mut d, side_order, f);
+                self.items[i][f] = d;
+            }
+        }
+
+        // word alignment
+        data += data & 1;
+
+        for f in 0..files {
+            for i in 0..sides {
+                let mut d = self.items[i][f].clone();
+                data = self.set_sizes(&mut d, data)?;
+                self.items[i][f] = d;
+            }
+        }
+
+        if self.dtz {
+            data = self.set_dtz_map(data, files);
+        }
+
+        for f in 0..files {
+            for i in 0..sides {
+                self.items[i][f].sparse_index = data;
+                data += self.items[i][f].sparse_index_size as usize * 6;
+            }
+        }
+
+        for f in 0..files {
+            for i in 0..sides {
+                self.items[i][f].block_length = data;
+                data += self.items[i][f].block_length_size as usize * 2;
+            }
+        }
+
+        for f in 0..files {
+            for i in 0..sides {
+                // 64 byte alignment
+                data = (data + 0x3F) & !0x3F;
+                self.items[i][f].data = data;
+                data += (self.items[i][f].num_blocks * self.items[i][f].block_size) as usize;
+            }
+        }
+
+        if data > self.bytes.len() {
+            return None;
+        }
+        Some(())
+    }
+
+    // groups the pieces that are encoded together and calculates the size of every group
+    // a group is pieces of the same type and color, except the first group which is the leading pawns,
+    // three unique pieces or the two kings
+    fn set_groups(&self, d: &mut PairsData, order: &[u8; 2], f: usize) {
+        let maps = maps();
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns { 0 } else if self.has_unique_pieces { 3 } else { 2 };
+        d.group_len[n] = 1;
+
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        // the groups are encoded in the order given by the file
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if pp { 2 } else { 1 };
+        let mut free_squares = 64 - d.group_len[0] - if pp { d.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                // leading pawns or pieces
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    maps.lead_pawns_size[d.group_len[0]][f]
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                // remaining pawns
+                d.group_idx[1] = idx;
+                idx *= maps.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                // remaining pieces
+                d.group_idx[next] = idx;
+                idx *= maps.binomial[d.group_len[next]][free_squares];
+                free_squares -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+
+        d.group_idx[n] = idx;
+    }
+
+    // reads the sizes and the huffman code of a sub-table, returns the offset after it
+    fn set_sizes(&self, d: &mut PairsData, mut data: usize) -> Option<usize> {
+        d.flags = self.byte(data);
+        data += 1;
+
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            // the single value is stored in min_sym_len
+            d.min_sym_len = self.byte(data);
+            return Some(data + 1);
+        }
+
+        let groups = d.group_len.iter().position(|len| *len == 0).unwrap_or(TB_PIECES);
+        let tb_size = d.group_idx[groups];
+
+        d.block_size = 1 << self.byte(data);
+        d.span = 1 << self.byte(data + 1);
+        d.sparse_index_size = tb_size.div_ceil(d.span);
+        let padding = self.byte(data + 2) as u64;
+        d.num_blocks = self.u32_le(data + 3) as u64;
+        d.block_length_size = d.num_blocks + padding;
+        d.max_sym_len = self.byte(data + 7);
+        d.min_sym_len = self.byte(data + 8);
+        data += 9;
+        d.lowest_sym = data;
+
+        if d.max_sym_len < d.min_sym_len || d.min_sym_len == 0 {
+            return None;
+        }
+
+        // canonical huffman code, longer symbols have lower values
+        let lengths = (d.max_sym_len - d.min_sym_len + 1) as usize;
+        d.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = self.u16_le(d.lowest_sym + 2 * i) as u64;
+            let lowest_next = self.u16_le(d.lowest_sym + 2 * (i + 1)) as u64;
+            d.base64[i] = d.base64[i + 1].wrapping_add(lowest).wrapping_sub(lowest_next) / 2;
+        }
+        for i in 0..lengths {
+            let shift = 64 - i as u32 - d.min_sym_len as u32;
+            d.base64[i] = d.base64[i].checked_shl(shift).unwrap_or(0);
+        }
+
+        data += lengths * 2;
+        let symbols = self.u16_le(data) as usize;
+        data += 2;
+        d.btree = data;
+
+        // recursive pairing, every symbol is a pair of two other symbols
+        d.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for sym in 0..symbols {
+            if !visited[sym] {
+                let len = self.set_symlen(d, sym, &mut visited)?;
+                d.symlen[sym] = len;
+            }
+        }
+
+        Some(data + symbols * 3 + (symbols & 1))
+    }
+
+    fn set_symlen(&self, d: &mut PairsData, sym: usize, visited: &mut [bool]) -> Option<u8> {
+        visited[sym] = true;
+        let (left, right) = self.btree(d, sym);
+        if right == 0xFFF {
+            return Some(0);
+        }
+        if left >= visited.len() || right >= visited.len() {
+            return None;
+        }
+
+        if !visited[left] {
+            let len = self.set_symlen(d, left, visited)?;
+            d.symlen[left] = len;
+        }
+        if !visited[right] {
+            let len = self.set_symlen(d, right, visited)?;
+            d.symlen[right] = len;
+        }
+        Some(d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1))
+    }
+
+    // the left and right symbol of a pair, 12 bits each
+    fn btree(&self, d: &PairsData, sym: usize) -> (usize, usize) {
+        let offset = d.btree + 3 * sym;
+        let (b0, b1, b2) = (self.byte(offset) as usize, self.byte(offset + 1) as usize, self.byte(offset + 2) as usize);
+        (((b1 & 0xF) << 8) | b0, (b2 << 4) | (b1 >> 4))
+    }
+
+    // dtz tables can map the stored values to the real ones, one map for each wdl
+    fn set_dtz_map(&mut self, mut data: usize, files: usize) -> usize {
+        for f in 0..files {
+            let flags = self.items[0][f].flags;
+            if flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+            if flags & FLAG_WIDE != 0 {
+                data += data & 1;
+                for i in 0..4 {
+                    self.items[0][f].map_idx[i] = data + 2;
+                    data += 2 * self.u16_le(data) as usize + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    self.items[0][f].map_idx[i] = data + 1;
+                    data += self.byte(data) as usize + 1;
+                }
+            }
+        }
+        data + (data & 1)
+    }
+
+    // returns the value at idx in a sub-table
+    fn decompress_pairs(&self, d: &PairsData, idx: u64) -> Option<u16> {
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(d.min_sym_len as u16);
+        }
+
+        // find the block using the sparse index, which points into the middle of every span
+        let k = idx / d.span;
+        if k >= d.sparse_index_size {
+            return None;
+        }
+        let mut block = self.u32_le(d.sparse_index + 6 * k as usize) as i64;
+        let mut offset = self.u16_le(d.sparse_index + 6 * k as usize + 4) as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+
+        let block_length = |block: i64| self.u16_le(d.block_length + 2 * block as usize) as i64;
+        while offset < 0 {
+            block -= 1;
+            if block < 0 {
+                return None;
+            }
+            offset += block_length(block) + 1;
+        }
+        while offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+            if block as u64 >= d.block_length_size {
+                return None;
+            }
+        }
+
+        // read huffman symbols from the block until the one containing our value
+        let mut ptr = d.data + block as usize * d.block_size as usize;
+        let mut buf64 = ((self.u32_be(ptr) as u64) << 32) | self.u32_be(ptr + 4) as u64;
+        ptr += 8;
+        let mut buf64_size = 64;
+        let mut sym;
+
+        loop {
+            let mut len = 0;
+            while buf64 < d.base64[len] {
+                len += 1;
+                if len >= d.base64.len() {
+                    return None;
+                }
+            }
+
+            sym = ((buf64 - d.base64[len]) >> (64 - len - d.min_sym_len as usize)) as usize;
+            sym += self.u16_le(d.lowest_sym + 2 * len) as usize;
+            if sym >= d.symlen.len() {
+                return None;
+            }
+
+            if offset < d.symlen[sym] as i64 + 1 {
+                break;
+            }
+
+            offset -= d.symlen[sym] as i64 + 1;
+            len += d.min_sym_len as usize;
+            buf64 = buf64.checked_shl(len as u32).unwrap_or(0);
+            buf64_size -= len as i32;
+
+            if buf64_size <= 32 {
+                buf64_size += 32;
+                buf64 |= (self.u32_be(ptr) as u64) << (64 - buf64_size);
+                ptr += 4;
+            }
+        }
+
+        // expand the pairs until the single value
+        while d.symlen[sym] != 0 {
+            let (left, right) = self.btree(d, sym);
+            if offset < d.symlen[left] as i64 + 1 {
+                sym = left;
+            } else {
+                offset -= d.symlen[left] as i64 + 1;
+                sym = right;
+            }
+        }
+
+        Some(self.btree(d, sym).0 as u16)
+    }
+
+    // looks up a position, key is the material with white first
+    // returns the wdl (-2 to 2) or the dtz, and true if a dtz table stores the other side to move
+    fn probe(&self, game: &Game, key: &str, wdl: i32) -> Option<(i32, bool)> {
+        let maps = maps();
+        let side_to_move = if game.turn == PieceColor::White { 0 } else { 1 };
+
+        // tables for equal material only store white to move, and all tables are stored with the stronger side
+        // as white, if needed the colors are swapped and the board flipped
+        let symmetric_black_to_move = self.key == self.key2 && side_to_move == 1;
+        let black_stronger = key != self.key;
+        let flip = symmetric_black_to_move || black_stronger;
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = (flip as usize) ^ side_to_move;
+
+        let mut squares = [0usize; TB_PIECES];
+        let mut pieces = [0u8; TB_PIECES];
+        let mut size = 0;
+        let mut lead_pawns_count = 0;
+        let mut lead_pawns = 0u64;
+        let mut tb_file = 0;
+
+        if self.has_pawns {
+            // the leading pawns are first in the piece order, their color is the reference
+            let lead_color = self.items[0][0].pieces[0] ^ flip_color;
+            for s in 0..64 {
+                if let Some(piece) = game.live_pieces.get(&Square::at_index(s as i8)) {
+                    if piece_code(piece.piece_type, piece.color) == lead_color {
+                        if size >= TB_PIECES {
+                            return None;
+                        }
+                        lead_pawns |= 1 << s;
+                        squares[size] = s ^ flip_squares;
+                        size += 1;
+                    }
+                }
+            }
+            lead_pawns_count = size;
+
+            // the leading pawn is the one with the highest map_pawns
+            let mut lead = 0;
+            for i in 1..lead_pawns_count {
+                if maps.map_pawns[squares[i]] > maps.map_pawns[squares[lead]] {
+                    lead = i;
+                }
+            }
+            squares.swap(0, lead);
+            tb_file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+
+        // dtz tables only store one side to move
+        if self.dtz {
+            let flags = self.items[0][tb_file].flags;
+            if (flags & FLAG_STM) as usize != stm && (self.key != self.key2 || self.has_pawns) {
+                return Some((0, true));
+            }
+        }
+
+        for s in 0..64 {
+            if lead_pawns & (1 << s) != 0 {
+                continue;
+            }
+            if let Some(piece) = game.live_pieces.get(&Square::at_index(s as i8)) {
+                if size >= TB_PIECES {
+                    return None;
+                }
+                squares[size] = s ^ flip_squares;
+                pieces[size] = piece_code(piece.piece_type, piece.color) ^ flip_color;
+                size += 1;
+            }
+        }
+        if size != self.piece_count {
+            return None;
+        }
+
+        let d = if self.dtz { &self.items[0][tb_file] } else { &self.items[stm.min(self.items.len() - 1)][tb_file] };
+
+        // put the pieces in the order of the table
+        for i in lead_pawns_count..size.saturating_sub(1) {
+            for j in i + 1..size {
+                if d.pieces[i] == pieces[j] {
+                    pieces.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        // mirror so the leading piece is on files a-d
+        if file_of(squares[0]) > 3 {
+            for square in squares.iter_mut().take(size) {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx: u64;
+        if self.has_pawns {
+            idx = maps.lead_pawn_idx[lead_pawns_count][squares[0]];
+            squares[1..lead_pawns_count].sort_by_key(|s| maps.map_pawns[*s]);
+            for (i, square) in squares.iter().enumerate().take(lead_pawns_count).skip(1) {
+                idx += maps.binomial[i][maps.map_pawns[*square]];
+            }
+        } else {
+            // mirror so the leading piece is on ranks 1-4
+            if rank_of(squares[0]) > 3 {
+                for square in squares.iter_mut().take(size) {
+                    *square ^= 56;
+                }
+            }
+
+            // the first piece of the leading group that is not on the a1-h8 diagonal must be below it
+            for i in 0..d.group_len[0] {
+                if off_a1h8(squares[i]) == 0 {
+                    continue;
+                }
+                if off_a1h8(squares[i]) > 0 {
+                    for square in squares.iter_mut().take(size).skip(i) {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            if self.has_unique_pieces {
+                let adjust1 = (squares[1] > squares[0]) as usize;
+                let adjust2 = (squares[2] > squares[0]) as usize + (squares[2] > squares[1]) as usize;
+
+                idx = if off_a1h8(squares[0]) != 0 {
+                    ((maps.map_a1d1d4[squares[0]] * 63 + (squares[1] - adjust1)) * 62 + squares[2] - adjust2) as u64
+                } else if off_a1h8(squares[1]) != 0 {
+                    ((6 * 63 + rank_of(squares[0]) * 28 + maps.map_b1h1h7[squares[1]]) * 62 + squares[2] - adjust2) as u64
+                } else if off_a1h8(squares[2]) != 0 {
+                    (6 * 63 * 62 + 4 * 28 * 62 + rank_of(squares[0]) * 7 * 28 + (rank_of(squares[1]) - adjust1) * 28 + maps.map_b1h1h7[squares[2]]) as u64
+                } else {
+                    (6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank_of(squares[0]) * 7 * 6 + (rank_of(squares[1]) - adjust1) * 6 + (rank_of(squares[2]) - adjust2)) as u64
+                };
+            } else {
+                idx = maps.map_kk[maps.map_a1d1d4[squares[0]]][squares[1]] as u64;
+            }
+        }
+
+        // the remaining groups, the squares are counted without the ones taken by earlier groups
+        idx *= d.group_idx[0];
+        let mut group_start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let group_end = group_start + d.group_len[next];
+            if group_end > size {
+                return None;
+            }
+            squares[group_start..group_end].sort();
+
+            let mut n: u64 = 0;
+            for i in 0..d.group_len[next] {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start].iter().filter(|s| square > **s).count();
+                let index = (square - adjust).checked_sub(if remaining_pawns { 8 } else { 0 })?;
+                n += maps.binomial[i + 1][index];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            group_start = group_end;
+            next += 1;
+        }
+
+        let value = self.decompress_pairs(d, idx)? as i32;
+
+        if !self.dtz {
+            return Some((value - 2, false));
+        }
+
+        // dtz, map the value and convert to plies
+        let flags = d.flags;
+        let mut value = value;
+        if flags & FLAG_MAPPED != 0 {
+            let map = match wdl {
+                -2 => 1,
+                -1 => 3,
+                1 => 2,
+                _ => 0,
+            };
+            value = if flags & FLAG_WIDE != 0 {
+                self.u16_le(d.map_idx[map] + 2 * value as usize) as i32
+            } else {
+                self.byte(d.map_idx[map] + value as usize) as i32
+            };
+        }
+
+        if (wdl == 2 && flags & FLAG_WIN_PLIES == 0) || (wdl == -2 && flags & FLAG_LOSS_PLIES == 0) || wdl == 1 || wdl == -1 {
+            value *= 2;
+        }
+
+        Some((value + 1, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maps() {
+        let maps = maps();
+
+        // 462 ways to place two kings, 10 squares in the triangle
+        let max_kk = maps.map_kk.iter().flat_map(|row| row.iter()).max().unwrap();
+        assert_eq!(*max_kk, 461);
+        assert_eq!(maps.map_a1d1d4[Square::B1.to_index() as usize], 0);
+        assert_eq!(maps.map_a1d1d4[Square::D4.to_index() as usize], 9);
+        assert_eq!(maps.map_b1h1h7[Square::H7.to_index() as usize], 27);
+
+        // pawns on the edge lead
+        assert_eq!(maps.map_pawns[Square::A2.to_index() as usize], 47);
+        assert_eq!(maps.map_pawns[Square::H2.to_index() as usize], 46);
+        assert_eq!(maps.map_pawns[Square::E2.to_index() as usize], 10);
+        assert_eq!(maps.map_pawns[Square::E7.to_index() as usize], 0);
+
+        assert_eq!(maps.binomial[2][10], 45);
+        assert_eq!(maps.binomial[5][63], 7028847);
+        assert_eq!(maps.lead_pawns_size[1][0], 6);
+    }
+
+    #[test]
+    fn test_probe_without_files() {
+        assert!(Tablebase::open("/this/directory/does/not/exist").is_err());
+
+        let dir = std::env::temp_dir().join("oscae_chess_empty_syzygy");
+        fs::create_dir_all(&dir).unwrap();
+        let tb = Tablebase::open(dir.to_str().unwrap()).unwrap();
+        assert_eq!(tb.max_pieces(), 0);
+
+        // too many pieces or missing tables
+        assert!(tb.probe_wdl(&Game::new()).is_none());
+        assert!(tb.probe(&Game::from_fen("8/8/8/4k3/8/8/3Q4/4K3 w - - 0 1")).is_none());
+
+        let game = Game::from_fen("8/8/8/4k3/8/8/3Q4/4K3 w - - 0 1");
+        assert_eq!(material_codes(&game), ("KQ".to_string(), "K".to_string()));
+
+        let mut engine = crate::search::Engine::new();
+        assert!(engine.set_option("SyzygyPath", "/this/directory/does/not/exist").is_err());
+        assert!(engine.set_option("SyzygyPath", dir.to_str().unwrap()).is_ok());
+        assert!(engine.tablebase().is_some());
+    }
+
+    #[test]
+    fn test_broken_file() {
+        let dir = std::env::temp_dir().join("oscae_chess_broken_syzygy");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("KQvK.rtbw"), [0u8; 64]).unwrap();
+        fs::write(dir.join("KQvK.rtbz"), [0u8; 64]).unwrap();
+
+        let tb = Tablebase::open(dir.to_str().unwrap()).unwrap();
+        assert_eq!(tb.max_pieces(), 3);
+
+        // wrong magic, the table is treated as missing
+        let game = Game::from_fen("8/8/8/4k3/8/8/3Q4/4K3 w - - 0 1");
+        assert!(tb.probe_wdl(&game).is_none());
+        assert!(tb.best_move(&game).is_none());
+    }
+
+    #[test]
+    fn test_probe_tables() {
+        // KQvK and KRvK tables written by testdata/syzygy/generate.py
+        let tb = Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/syzygy")).unwrap();
+        assert_eq!(tb.max_pieces(), 3);
+        let probe = |fen: &str| {
+            let game = Game::from_fen(fen);
+            (tb.probe_wdl(&game).unwrap(), tb.probe_dtz(&game).unwrap())
+        };
+
+        // white to move is stored in the dtz tables, mate in one and the longest wins
+        assert_eq!(probe("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"), (Wdl::Win, 1));
+        assert_eq!(probe("8/8/8/8/8/8/6k1/K1R5 w - - 0 1"), (Wdl::Win, 19));
+        assert_eq!(probe("8/8/8/8/3k4/8/1R6/K7 w - - 0 1"), (Wdl::Win, 31));
+
+        // black to move is only in the wdl tables, the dtz comes from a search of the moves
+        assert_eq!(probe("8/8/8/8/4k3/8/1Q6/K7 b - - 0 1"), (Wdl::Loss, -20));
+        assert_eq!(probe("8/8/8/8/8/8/1Rk5/K7 b - - 0 1"), (Wdl::Loss, -32));
+        assert_eq!(probe("R6k/8/6K1/8/8/8/8/8 b - - 0 1"), (Wdl::Loss, -1));
+
+        // taking the unprotected queen draws
+        assert_eq!(probe("8/8/8/8/8/8/1kQ5/4K3 b - - 0 1"), (Wdl::Draw, 0));
+
+        // black is the stronger side, the colors are swapped to find the table
+        assert_eq!(probe("k7/1q6/8/4K3/8/8/8/8 w - - 0 1"), (Wdl::Loss, -20));
+
+        let game = Game::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1");
+        let (m, result) = tb.best_move(&game).unwrap();
+        assert!(m.from == Square::B1 && m.to == Square::B8);
+        assert_eq!(result, TbResult { wdl: Wdl::Win, dtz: 1 });
+        assert_eq!(tb.probe(&game).unwrap(), TbResult { wdl: Wdl::Win, dtz: 1 });
+
+        // the fifty-move counter turns a long win into a cursed win
+        let game = Game::from_fen("8/8/8/8/3k4/8/1R6/K7 w - - 80 100");
+        assert_eq!(tb.probe(&game).unwrap().wdl, Wdl::CursedWin);
+    }
+}
@@ -0,0 +1,391 @@
+// A game tree with variations and annotations, for studies and analysis.
+//
+// Every node except the root holds a move and the moves that can follow it. The first child is the main line, the
+// others are variations in the order they are shown. Nodes are kept in a map and referred to by NodeId, an id stays
+// the same until the node is deleted.
+//
+// Comments, NAGs (the $1 of PGN, see the NAG_ constants) and the [%clk] and [%eval] commands that lichess and
+// chess.com put into PGN comments are kept on the node of the move they belong to.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{Game, Move};
+
+// Numeric Annotation Glyphs
+pub const NAG_GOOD_MOVE: u8 = 1; // !
+pub const NAG_MISTAKE: u8 = 2; // ?
+pub const NAG_BRILLIANT_MOVE: u8 = 3; // !!
+pub const NAG_BLUNDER: u8 = 4; // ??
+pub const NAG_SPECULATIVE_MOVE: u8 = 5; // !?
+pub const NAG_DUBIOUS_MOVE: u8 = 6; // ?!
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+// an evaluation as written in [%eval], from the point of view of white
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Evaluation {
+    Centipawns(i32),
+    // moves to mate, negative if black mates
+    Mate(i32),
+}
+
+#[derive(Clone, Debug)]
+pub struct Node {
+    m: Option<Move>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    pub comment: Option<String>,
+    pub nags: Vec<u8>,
+    // the time left after the move
+    pub clock: Option<Duration>,
+    pub eval: Option<Evaluation>,
+}
+
+impl Node {
+    fn new(m: Option<Move>, parent: Option<NodeId>) -> Self {
+        Self { m, parent, children: Vec::new(), comment: None, nags: Vec::new(), clock: None, eval: None }
+    }
+
+    // the move that leads to this node, None for the root
+    pub fn get_move(&self) -> Option<Move> {
+        self.m
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    // the main line first, then the variations
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    // sets the comment, clock and eval from a PGN comment, [%clk 1:02:03] and [%eval 0.35] or [%eval #-3] are read into clock and eval
+    pub fn set_pgn_comment(&mut self, text: &str) {
+        let mut rest = String::new();
+        let mut text = text;
+        self.clock = None;
+        self.eval = None;
+
+        while let Some(start) = text.find("[%") {
+            let end = match text[start..].find(']') {
+                Some(end) => start + end,
+                None => break,
+            };
+            rest.push_str(&text[..start]);
+            let mut command = text[start + 2..end].split_whitespace();
+            match (command.next(), command.next()) {
+                (Some("clk"), Some(value)) if parse_clock(value).is_some() => self.clock = parse_clock(value),
+                (Some("eval"), Some(value)) if parse_eval(value).is_some() => self.eval = parse_eval(value),
+                // other commands such as [%csl] stay in the comment
+                _ => rest.push_str(&text[start..=end]),
+            }
+            text = &text[end + 1..];
+        }
+        rest.push_str(text);
+
+        let rest = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+        self.comment = if rest.is_empty() { None } else { Some(rest) };
+    }
+
+    // returns the comment as written in PGN (without the braces), with the eval and clock first
+    pub fn pgn_comment(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(eval) = self.eval {
+            parts.push(match eval {
+                Evaluation::Centipawns(cp) => format!("[%eval {}{}.{:02}]", if cp < 0 { "-" } else { "" }, cp.abs() / 100, cp.abs() % 100),
+                Evaluation::Mate(n) => format!("[%eval #{}]", n),
+            });
+        }
+        if let Some(clock) = self.clock {
+            let seconds = clock.as_secs();
+            parts.push(format!("[%clk {}:{:02}:{:02}]", seconds / 3600, seconds / 60 % 60, seconds % 60));
+        }
+        if let Some(comment) = &self.comment {
+            parts.push(comment.clone());
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameTree {
+    start: Game,
+    nodes: HashMap<NodeId, Node>,
+    root: NodeId,
+    current: NodeId,
+    next_id: usize,
+}
+
+impl GameTree {
+    // creates a tree starting from the position of the game, the moves already played are not part of it
+    pub fn new(start: Game) -> Self {
+        let root = NodeId(0);
+        let mut nodes = HashMap::new();
+        nodes.insert(root, Node::new(None, None));
+        Self { start, nodes, root, current: root, next_id: 1 }
+    }
+
+    // the position the tree starts from
+    pub fn start(&self) -> &Game {
+        &self.start
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    // the node that is shown, moves are added after it
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(&id)
+    }
+
+    // returns the nodes from the first move to the node, without the root
+    pub fn path(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = Vec::new();
+        let mut node = Some(id);
+        while let Some(id) = node.filter(|id| *id != self.root) {
+            path.push(id);
+            node = self.nodes.get(&id).and_then(|n| n.parent);
+        }
+        path.reverse();
+        path
+    }
+
+    // returns the moves that lead to the node
+    pub fn moves_to(&self, id: NodeId) -> Vec<Move> {
+        self.path(id).iter().filter_map(|id| self.nodes[id].m).collect()
+    }
+
+    // returns the position after the node
+    pub fn game_at(&self, id: NodeId) -> Option<Game> {
+        if !self.nodes.contains_key(&id) {
+            return None;
+        }
+        let mut game = self.start.clone();
+        for m in self.moves_to(id) {
+            game.make_move(&m);
+        }
+        Some(game)
+    }
+
+    // returns the position after the current node
+    pub fn game(&self) -> Game {
+        self.game_at(self.current).unwrap()
+    }
+
+    // returns the main line from the root, or the line that continues after a node
+    pub fn main_line(&self, from: NodeId) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut node = from;
+        while let Some(child) = self.nodes.get(&node).and_then(|n| n.children.first()) {
+            line.push(*child);
+            node = *child;
+        }
+        line
+    }
+
+    // plays a move after the current node and makes it the current node
+    // a move that is already there is reused, otherwise it becomes the main line if there is none or the last variation
+    // returns None if the move is not legal
+    pub fn add_move(&mut self, m: &Move) -> Option<NodeId> {
+        if let Some(child) = self.nodes[&self.current].children.iter().find(|child| self.nodes[*child].m == Some(*m)) {
+            self.current = *child;
+            return Some(*child);
+        }
+
+        let mut game = self.game();
+        if !game.make_move(m) {
+            return None;
+        }
+
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(id, Node::new(Some(*m), Some(self.current)));
+        self.nodes.get_mut(&self.current).unwrap().children.push(id);
+        self.current = id;
+        Some(id)
+    }
+
+    // moves the node one place up among its siblings, returns false if it is already the main line
+    pub fn promote_variation(&mut self, id: NodeId) -> bool {
+        let parent = match self.nodes.get(&id).and_then(|n| n.parent) {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let siblings = &mut self.nodes.get_mut(&parent).unwrap().children;
+        let index = siblings.iter().position(|sibling| *sibling == id).unwrap();
+        if index == 0 {
+            return false;
+        }
+        siblings.swap(index, index - 1);
+        true
+    }
+
+    // makes the line to the node the main line, every node on the way becomes the first child of its parent
+    pub fn make_main_line(&mut self, id: NodeId) {
+        for node in self.path(id) {
+            let parent = self.nodes[&node].parent.unwrap();
+            let siblings = &mut self.nodes.get_mut(&parent).unwrap().children;
+            let index = siblings.iter().position(|sibling| *sibling == node).unwrap();
+            let node = siblings.remove(index);
+            siblings.insert(0, node);
+        }
+    }
+
+    // deletes the node and everything after it, the root can not be deleted
+    // if the current node is deleted, its parent becomes the current node
+    pub fn delete(&mut self, id: NodeId) -> bool {
+        let parent = match self.nodes.get(&id).and_then(|n| n.parent) {
+            Some(parent) => parent,
+            None => return false,
+        };
+
+        if self.path(self.current).contains(&id) {
+            self.current = parent;
+        }
+
+        self.nodes.get_mut(&parent).unwrap().children.retain(|child| *child != id);
+        let mut removed = vec![id];
+        while let Some(node) = removed.pop() {
+            if let Some(node) = self.nodes.remove(&node) {
+                removed.extend(node.children);
+            }
+        }
+        true
+    }
+
+    // makes the node the current node, returns false if it does not exist
+    pub fn go_to(&mut self, id: NodeId) -> bool {
+        if !self.nodes.contains_key(&id) {
+            return false;
+        }
+        self.current = id;
+        true
+    }
+
+    // goes back one move, returns false at the root
+    pub fn go_back(&mut self) -> bool {
+        match self.nodes[&self.current].parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // goes forward along the main line, returns false at its end
+    pub fn go_forward(&mut self) -> bool {
+        match self.nodes[&self.current].children.first() {
+            Some(child) => {
+                self.current = *child;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.current = self.root;
+    }
+
+    // goes to the end of the line that continues after the current node
+    pub fn go_to_end(&mut self) {
+        while self.go_forward() {}
+    }
+}
+
+// reads "1:02:03" or "0:05:30.2", tenths are dropped
+fn parse_clock(value: &str) -> Option<Duration> {
+    let mut seconds = 0;
+    for part in value.split('.').next()?.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+// reads "0.35", "-1.2" or "#-3"
+fn parse_eval(value: &str) -> Option<Evaluation> {
+    match value.strip_prefix('#') {
+        Some(moves) => moves.parse().ok().map(Evaluation::Mate),
+        None => value.parse::<f64>().ok().map(|pawns| Evaluation::Centipawns((pawns * 100.0).round() as i32)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(tree: &mut GameTree, uci: &str) -> NodeId {
+        tree.add_move(&Move::from_uci(uci).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut tree = GameTree::new(Game::new());
+        let e4 = add(&mut tree, "e2e4");
+        let e5 = add(&mut tree, "e7e5");
+        tree.go_back();
+        let c5 = add(&mut tree, "c7c5");
+        let nf3 = add(&mut tree, "g1f3");
+        assert!(tree.add_move(&Move::from_uci("e1e3").unwrap()).is_none());
+
+        assert_eq!(tree.get(e4).unwrap().children(), [e5, c5]);
+        assert_eq!(tree.main_line(tree.root()), vec![e4, e5]);
+        assert!(tree.moves_to(nf3).len() == 3 && tree.game().to_fen() == tree.game_at(nf3).unwrap().to_fen());
+
+        // playing a move that is there goes to it
+        tree.go_to(e4);
+        assert_eq!(add(&mut tree, "e7e5"), e5);
+
+        assert!(tree.promote_variation(c5) && !tree.promote_variation(c5));
+        assert_eq!(tree.main_line(tree.root()), vec![e4, c5, nf3]);
+        tree.make_main_line(e5);
+        assert_eq!(tree.main_line(tree.root()), vec![e4, e5]);
+
+        tree.go_to(nf3);
+        assert!(tree.delete(c5) && !tree.delete(tree.root()));
+        assert!(tree.current() == e4 && tree.get(nf3).is_none());
+        assert_eq!(tree.get(e4).unwrap().children(), [e5]);
+
+        tree.go_to_start();
+        tree.go_to_end();
+        assert_eq!(tree.current(), e5);
+        assert!(tree.go_back() && tree.go_back() && !tree.go_back());
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut tree = GameTree::new(Game::new());
+        let e4 = add(&mut tree, "e2e4");
+        let node = tree.get_mut(e4).unwrap();
+        node.nags.push(NAG_GOOD_MOVE);
+        node.set_pgn_comment(" [%eval 0.35] best by test [%clk 1:02:03] [%csl Ge4] ");
+        assert_eq!(node.eval, Some(Evaluation::Centipawns(35)));
+        assert_eq!(node.clock, Some(Duration::from_secs(3723)));
+        assert_eq!(node.comment.as_deref(), Some("best by test [%csl Ge4]"));
+        assert_eq!(node.pgn_comment().unwrap(), "[%eval 0.35] [%clk 1:02:03] best by test [%csl Ge4]");
+
+        node.set_pgn_comment("[%eval #-3]");
+        assert!(node.eval == Some(Evaluation::Mate(-3)) && node.comment.is_none());
+        assert!(node.clock.is_none());
+        node.eval = Some(Evaluation::Centipawns(-5));
+        assert_eq!(node.pgn_comment().unwrap(), "[%eval -0.05]");
+    }
+}
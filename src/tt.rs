@@ -0,0 +1,374 @@
+// Transposition table, caches search results per position using Game::hash() as key.
+//
+// The table has a fixed size that is a power of two and is split into buckets of ENTRIES_PER_BUCKET entries.
+// Each entry is two u64, the key and the data. To be able to share the table between threads without locks
+// the key is stored XORed with the data, if another thread writes the same entry at the same time the key will no
+// longer match when probing and the torn entry is simply treated as a miss.
+//
+// Replacement: an entry for the same position is overwritten unless the stored result is deeper and from the current
+// search. Otherwise the entry with the lowest depth, counting old entries as less valuable, is replaced.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::{Move, PieceType, Square};
+
+// scores above MATE_BOUND (or below -MATE_BOUND) are mate scores, MATE_SCORE - ply is mate in ply half-moves
+pub const MATE_SCORE: i32 = 30000;
+pub const MATE_BOUND: i32 = MATE_SCORE - 1000;
+
+const ENTRIES_PER_BUCKET: usize = 4;
+const ENTRY_BYTES: usize = 16;
+
+// tells how the stored score relates to the real score of the position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    // the score is exact
+    Exact,
+    // the real score is at least the stored score (the search failed high)
+    Lower,
+    // the real score is at most the stored score (the search failed low)
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    pub depth: u8,
+    pub bound: Bound,
+    pub score: i32,
+}
+
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    // number of buckets - 1, used to pick a bucket from a hash
+    mask: usize,
+    // incremented every search so old entries can be recognized
+    generation: AtomicU8,
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TranspositionTable").field("size_bytes", &self.size_bytes()).field("hashfull", &self.hashfull()).finish()
+    }
+}
+
+impl TranspositionTable {
+    // creates a table using at most mb megabytes, the number of buckets is rounded down to a power of two
+    pub fn new(mb: usize) -> Self {
+        let buckets = bucket_count(mb);
+        let mut slots = Vec::with_capacity(buckets * ENTRIES_PER_BUCKET);
+        for _ in 0..buckets * ENTRIES_PER_BUCKET {
+            slots.push(Slot { key: AtomicU64::new(0), data: AtomicU64::new(0) });
+        }
+
+        Self { slots, mask: buckets - 1, generation: AtomicU8::new(0) }
+    }
+
+    // changes the size of the table, this clears all entries
+    pub fn resize(&mut self, mb: usize) {
+        *self = Self::new(mb);
+    }
+
+    // returns the size of the table in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.slots.len() * ENTRY_BYTES
+    }
+
+    // removes all entries
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    // should be called before every new search so entries from earlier searches are replaced first
+    pub fn new_search(&self) {
+        let next = (self.generation.load(Ordering::Relaxed) + 1) & GENERATION_MASK;
+        self.generation.store(next, Ordering::Relaxed);
+    }
+
+    // looks up a position, ply is the distance from the root of the search and is used to adjust mate scores
+    pub fn probe(&self, hash: u64, ply: u32) -> Option<TtEntry> {
+        let bucket = self.bucket(hash);
+        for slot in &self.slots[bucket..bucket + ENTRIES_PER_BUCKET] {
+            let data = slot.data.load(Ordering::Relaxed);
+            let key = slot.key.load(Ordering::Relaxed);
+            if data != 0 && key ^ data == hash {
+                let mut entry = unpack(data);
+                entry.score = score_from_tt(entry.score, ply);
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    // stores the result of searching a position, ply is the distance from the root of the search
+    pub fn store(&self, hash: u64, ply: u32, entry: TtEntry) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let bucket = self.bucket(hash);
+
+        let mut replace = bucket;
+        let mut lowest_value = i32::MAX;
+        let mut previous = None;
+
+        for i in bucket..bucket + ENTRIES_PER_BUCKET {
+            let data = self.slots[i].data.load(Ordering::Relaxed);
+            let key = self.slots[i].key.load(Ordering::Relaxed);
+
+            // empty entry, use it unless the same position is stored later in the bucket
+            if data == 0 {
+                if lowest_value != i32::MIN {
+                    replace = i;
+                    lowest_value = i32::MIN;
+                }
+                continue;
+            }
+
+            // same position
+            if key ^ data == hash {
+                replace = i;
+                previous = Some((unpack(data), data_generation(data)));
+                break;
+            }
+
+            // older entries are worth less than newer ones of the same depth
+            let age = (generation.wrapping_sub(data_generation(data)) & GENERATION_MASK) as i32;
+            let value = unpack(data).depth as i32 - 8 * age;
+            if value < lowest_value {
+                replace = i;
+                lowest_value = value;
+            }
+        }
+
+        let mut entry = entry;
+        if let Some((old, old_generation)) = previous {
+            // keep a deeper result from this search unless the new one is exact
+            if entry.bound != Bound::Exact && old_generation == generation && old.depth > entry.depth.saturating_add(2) {
+                return;
+            }
+            // keep the old best move if the new result has none
+            if entry.best_move.is_none() {
+                entry.best_move = old.best_move;
+            }
+        }
+
+        entry.score = score_to_tt(entry.score, ply);
+        let data = pack(&entry, generation);
+        self.slots[replace].key.store(hash ^ data, Ordering::Relaxed);
+        self.slots[replace].data.store(data, Ordering::Relaxed);
+    }
+
+    // returns how full the table is in permille, counting only entries from the current search
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample].iter().filter(|slot| {
+            let data = slot.data.load(Ordering::Relaxed);
+            data != 0 && data_generation(data) == generation
+        }).count();
+        (used * 1000 / sample) as u32
+    }
+
+    fn bucket(&self, hash: u64) -> usize {
+        (hash as usize & self.mask) * ENTRIES_PER_BUCKET
+    }
+}
+
+// converts a score relative to the root to a score relative to the stored position
+pub fn score_to_tt(score: i32, ply: u32) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+// converts a stored score back to a score relative to the root
+pub fn score_from_tt(score: i32, ply: u32) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+fn bucket_count(mb: usize) -> usize {
+    let buckets = (mb * 1024 * 1024 / (ENTRY_BYTES * ENTRIES_PER_BUCKET)).max(1);
+    // round down to a power of two
+    1 << (usize::BITS - 1 - buckets.leading_zeros())
+}
+
+// data layout: bits 0-15 move, 16-23 depth, 24-25 bound, 26-31 generation, 32-47 score, the rest is always 1
+// so that an entry is never 0 (which marks an empty entry)
+const GENERATION_MASK: u8 = 0b111111;
+
+fn pack(entry: &TtEntry, generation: u8) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    let score = entry.score.clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16;
+
+    pack_move(entry.best_move) as u64 |
+        (entry.depth as u64) << 16 |
+        bound << 24 |
+        ((generation & GENERATION_MASK) as u64) << 26 |
+        (score as u64) << 32 |
+        0xFFFF << 48
+}
+
+fn unpack(data: u64) -> TtEntry {
+    let bound = match (data >> 24) & 0b11 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+
+    TtEntry {
+        best_move: unpack_move(data as u16),
+        depth: (data >> 16) as u8,
+        bound,
+        score: (data >> 32) as u16 as i16 as i32,
+    }
+}
+
+fn data_generation(data: u64) -> u8 {
+    (data >> 26) as u8 & GENERATION_MASK
+}
+
+// move layout: bits 0-5 from, 6-11 to, 12-14 promotion (6 is a king in Antichess), bit 15 drop with the piece in 12-14. 0 means no move
+fn pack_move(m: Option<Move>) -> u16 {
+    match m {
+        Some(Move { to, drop: Some(piece_type), .. }) => {
+            let piece = match piece_type {
+                PieceType::Queen => 1,
+                PieceType::Rook => 2,
+                PieceType::Bishop => 3,
+                PieceType::Knight => 4,
+                _ => 5,
+            };
+            to.to_index() as u16 | (to.to_index() as u16) << 6 | piece << 12 | 1 << 15
+        },
+        Some(m) => {
+            let promotion = match m.promotion {
+                None => 0,
+                Some(PieceType::Queen) => 1,
+                Some(PieceType::Rook) => 2,
+                Some(PieceType::Bishop) => 3,
+                Some(PieceType::Knight) => 4,
+                Some(PieceType::King) => 6,
+                Some(_) => 0,
+            };
+            m.from.to_index() as u16 | (m.to.to_index() as u16) << 6 | promotion << 12
+        },
+        None => 0,
+    }
+}
+
+fn unpack_move(value: u16) -> Option<Move> {
+    if value == 0 {
+        return None;
+    }
+
+    let from = Square::at_index((value & 0b111111) as i8);
+    let to = Square::at_index(((value >> 6) & 0b111111) as i8);
+    let piece = match (value >> 12) & 0b111 {
+        1 => Some(PieceType::Queen),
+        2 => Some(PieceType::Rook),
+        3 => Some(PieceType::Bishop),
+        4 => Some(PieceType::Knight),
+        5 => Some(PieceType::Pawn),
+        6 => Some(PieceType::King),
+        _ => None,
+    };
+
+    if value >> 15 != 0 {
+        return Some(Move { from, to, promotion: None, drop: piece });
+    }
+    Some(Move { from, to, promotion: piece, drop: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+
+    #[test]
+    fn test_store_probe() {
+        let tt = TranspositionTable::new(1);
+        assert_eq!(tt.size_bytes(), 1024 * 1024);
+
+        let game = Game::new();
+        let hash = game.hash();
+        assert!(tt.probe(hash, 0).is_none());
+
+        let best_move = Some(Move::new(Square::E2, Square::E4));
+        tt.store(hash, 0, TtEntry { best_move, depth: 5, bound: Bound::Lower, score: -42 });
+        let entry = tt.probe(hash, 0).unwrap();
+        assert_eq!(entry.best_move, best_move);
+        assert_eq!(entry.depth, 5);
+        assert_eq!(entry.bound, Bound::Lower);
+        assert_eq!(entry.score, -42);
+
+        // a shallower result of the same search does not replace a deeper one
+        tt.store(hash, 0, TtEntry { best_move: None, depth: 1, bound: Bound::Upper, score: 7 });
+        assert_eq!(tt.probe(hash, 0).unwrap().depth, 5);
+
+        // promotions survive packing
+        let promotion = Some(Move::with_promotion(Square::A7, Square::B8, PieceType::Knight));
+        tt.store(1234, 0, TtEntry { best_move: promotion, depth: 1, bound: Bound::Exact, score: 0 });
+        assert_eq!(tt.probe(1234, 0).unwrap().best_move, promotion);
+        let drop = Some(Move::new_drop(PieceType::Pawn, Square::A1));
+        tt.store(5678, 0, TtEntry { best_move: drop, depth: 1, bound: Bound::Exact, score: 0 });
+        assert_eq!(tt.probe(5678, 0).unwrap().best_move, drop);
+
+        tt.clear();
+        assert!(tt.probe(hash, 0).is_none());
+    }
+
+    #[test]
+    fn test_mate_scores() {
+        let tt = TranspositionTable::new(1);
+
+        // mate in 3 plies from the root, found at ply 2, is mate in 1 ply from the stored position
+        tt.store(99, 2, TtEntry { best_move: None, depth: 1, bound: Bound::Exact, score: MATE_SCORE - 3 });
+        // reached again at ply 4 it is mate in 5 plies from the root
+        assert_eq!(tt.probe(99, 4).unwrap().score, MATE_SCORE - 5);
+
+        assert_eq!(score_from_tt(score_to_tt(-MATE_SCORE + 10, 6), 6), -MATE_SCORE + 10);
+        assert_eq!(score_to_tt(150, 6), 150);
+    }
+
+    #[test]
+    fn test_hash() {
+        // the same position reached by different move orders has the same hash
+        let mut game1 = Game::new();
+        game1.do_move(&Square::G1, &Square::F3);
+        game1.do_move(&Square::G8, &Square::F6);
+        game1.do_move(&Square::B1, &Square::C3);
+        let mut game2 = Game::new();
+        game2.do_move(&Square::B1, &Square::C3);
+        game2.do_move(&Square::G8, &Square::F6);
+        game2.do_move(&Square::G1, &Square::F3);
+        assert_eq!(game1.hash(), game2.hash());
+        assert!(game1.hash() != Game::new().hash());
+
+        // castling rights are part of the hash
+        let with = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let without = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1");
+        assert!(with.hash() != without.hash());
+    }
+}
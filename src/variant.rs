@@ -0,0 +1,512 @@
+// Chess variants.
+//
+// A variant changes the rules of Game through a few hooks, everything else is shared with standard chess:
+//  - variant_moves() changes the legal moves of a piece, it is called at the end of legal_moves()
+//  - can_promote_to() and Variant::has_castling() change promotions and castling
+//  - variant_result() ends the game, it is called at the end of post_move() and can override the normal result
+//  - insufficient_material() tells when neither side can win anymore
+//  - variant_check() changes if the king is in check after a move
+//
+// The variant is chosen when the game is created with Game::new_variant() or Game::from_fen_variant().
+
+use crate::{ChessResult, Game, Piece, PieceColor, PieceType, Square};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    Standard,
+    // bringing the king to d4, e4, d5 or e5 wins
+    KingOfTheHill,
+    // giving check three times wins
+    ThreeCheck,
+    // captured pieces change sides and can be dropped back on the board
+    Crazyhouse,
+    // captures explode every piece next to them except pawns, exploding the king wins
+    Atomic,
+    // captures are compulsory and losing all pieces or being stalemated wins, the king is an ordinary piece
+    Antichess,
+    // white has 36 pawns and no king, black wins by capturing all of them and white by mating
+    Horde,
+    // the first king on the eighth rank wins, checks are not allowed
+    RacingKings,
+}
+
+// every variant, the binary formats store the index so the order must never change
+pub(crate) const VARIANTS: [Variant; 8] = [
+    Variant::Standard, Variant::KingOfTheHill, Variant::ThreeCheck, Variant::Crazyhouse, Variant::Atomic,
+    Variant::Antichess, Variant::Horde, Variant::RacingKings,
+];
+
+impl Variant {
+    // returns the variant with the given name, case, spaces and dashes are ignored ("King of the Hill", "kingofthehill")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name: String = name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase();
+        match name.as_str() {
+            "standard" | "chess" => Some(Variant::Standard),
+            "kingofthehill" | "koth" => Some(Variant::KingOfTheHill),
+            "threecheck" | "3check" => Some(Variant::ThreeCheck),
+            "crazyhouse" | "zh" => Some(Variant::Crazyhouse),
+            "atomic" => Some(Variant::Atomic),
+            "antichess" | "losingchess" | "giveaway" => Some(Variant::Antichess),
+            "horde" => Some(Variant::Horde),
+            "racingkings" => Some(Variant::RacingKings),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "Standard",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::ThreeCheck => "Three-check",
+            Variant::Crazyhouse => "Crazyhouse",
+            Variant::Atomic => "Atomic",
+            Variant::Antichess => "Antichess",
+            Variant::Horde => "Horde",
+            Variant::RacingKings => "Racing Kings",
+        }
+    }
+
+    // returns the FEN of the starting position
+    pub fn starting_fen(&self) -> &'static str {
+        match self {
+            Variant::Standard => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Variant::KingOfTheHill => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Variant::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0",
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Variant::Atomic => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Horde => "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1",
+            Variant::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+        }
+    }
+
+    pub fn has_castling(&self) -> bool {
+        !matches!(self, Variant::Antichess | Variant::RacingKings)
+    }
+}
+
+impl Game {
+    // changes the legal moves of a piece
+    pub(crate) fn variant_moves(&self, piece: &Piece, moves: u64) -> u64 {
+        match self.variant {
+            // only captures are allowed if there is any
+            Variant::Antichess if self.has_capture(piece.color) => self.captures(piece, moves),
+            // moves that give check are not allowed
+            Variant::RacingKings => {
+                let mut legal = moves;
+                for i in 0..64 {
+                    if (moves >> i) & 1 != 0 && self.gives_check(piece, Square::at_index(i)) {
+                        legal &= !(1 << i);
+                    }
+                }
+                legal
+            },
+            _ => moves,
+        }
+    }
+
+    // returns true if a pawn can be promoted to the piece type
+    pub(crate) fn can_promote_to(&self, class: PieceType) -> bool {
+        match class {
+            PieceType::Pawn => false,
+            PieceType::King => self.variant == Variant::Antichess,
+            _ => true,
+        }
+    }
+
+    // returns the result if the rules of the variant end the game
+    pub(crate) fn variant_result(&self) -> Option<ChessResult> {
+        match self.variant {
+            Variant::Standard | Variant::Crazyhouse => None,
+            // white has no king so it can only lose by running out of pieces
+            Variant::Horde => {
+                let white_left = self.live_pieces.values().any(|p| p.color == PieceColor::White);
+                if white_left { None } else { Some(ChessResult::BlackWon) }
+            },
+            // if white gets there first black has one move to also get there and draw
+            Variant::RacingKings => {
+                let on_goal = |color: PieceColor| self.live_pieces.values().any(|p| p.piece_type == PieceType::King && p.color == color && p.pos.y == 7);
+                match (on_goal(PieceColor::White), on_goal(PieceColor::Black)) {
+                    (true, true) => Some(ChessResult::Draw),
+                    (false, true) => Some(ChessResult::BlackWon),
+                    (true, false) if self.turn == PieceColor::Black && self.live_pieces.values().any(|p| {
+                        p.piece_type == PieceType::King && p.color == PieceColor::Black && self.legal_moves(p) & 0xFF00000000000000 != 0
+                    }) => None,
+                    (true, false) => Some(ChessResult::WhiteWon),
+                    (false, false) => None,
+                }
+            },
+            // the player to move wins without pieces or moves left
+            Variant::Antichess => {
+                let has_moves = self.live_pieces.values().any(|p| p.color == self.turn && self.legal_moves(p) != 0);
+                if has_moves { None } else { Some(win(self.turn)) }
+            },
+            // the side whose king is still on the board won
+            Variant::Atomic => {
+                let white_king = self.live_pieces.values().any(|p| p.piece_type == PieceType::King && p.color == PieceColor::White);
+                let black_king = self.live_pieces.values().any(|p| p.piece_type == PieceType::King && p.color == PieceColor::Black);
+                match (white_king, black_king) {
+                    (true, false) => Some(ChessResult::WhiteWon),
+                    (false, true) => Some(ChessResult::BlackWon),
+                    _ => None,
+                }
+            },
+            Variant::KingOfTheHill => self.live_pieces.values()
+                .find(|p| p.piece_type == PieceType::King && (3..=4).contains(&p.pos.x) && (3..=4).contains(&p.pos.y))
+                .map(|king| win(king.color)),
+            Variant::ThreeCheck => {
+                if self.white_checks >= 3 {
+                    Some(ChessResult::WhiteWon)
+                } else if self.black_checks >= 3 {
+                    Some(ChessResult::BlackWon)
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    // returns true if neither side has the pieces to win
+    pub(crate) fn insufficient_material(&self) -> bool {
+        match self.variant {
+            // a lone king can still walk to the center, captured pieces come back in Crazyhouse
+            // Horde is won by capturing or mating so there is always something to play for
+            Variant::KingOfTheHill | Variant::Crazyhouse | Variant::Antichess | Variant::Horde | Variant::RacingKings => false,
+            // a single minor piece can still give checks
+            Variant::ThreeCheck => self.live_pieces.len() <= 2,
+            // king against king and a minor piece or two kings, in Atomic a lone king has nothing to explode next to it
+            Variant::Standard | Variant::Atomic => self.live_pieces.len() <= 3 && self.live_pieces.values().all(|p| {
+                matches!(p.piece_type, PieceType::King | PieceType::Bishop | PieceType::Knight)
+            }),
+        }
+    }
+
+    // returns if the king of the player to move is in check, check is what the normal rules say
+    pub(crate) fn variant_check(&self, check: bool) -> bool {
+        match self.variant {
+            // kings next to each other can not be attacked since capturing would explode both
+            Variant::Atomic => check && !self.kings_touching(),
+            Variant::Antichess => false,
+            _ => check,
+        }
+    }
+
+    // removes the moves that lose the own king or leave it in check after the explosions in Atomic
+    // a move that explodes the other king is always legal, and the king is never in check when the kings touch
+    pub(crate) fn atomic_legal_moves(&self, piece: &Piece, moves: u64, own_king_bitmap: u64) -> u64 {
+        let (own_color_bitmap, other_color_bitmap) = match piece.color {
+            PieceColor::White => (self.white_bitmap, self.black_bitmap),
+            PieceColor::Black => (self.black_bitmap, self.white_bitmap),
+        };
+        let other_king_bitmap = self.live_pieces.values()
+            .find(|p| p.piece_type == PieceType::King && p.color != piece.color)
+            .map_or(0, |king| king.pos.to_bitmap());
+        let pawns_bitmap = self.live_pieces.values()
+            .filter(|p| p.piece_type == PieceType::Pawn)
+            .fold(0, |bitmap, p| bitmap | p.pos.to_bitmap());
+
+        let mut legal = moves;
+        for i in 0..64 {
+            if (moves >> i) & 1 == 0 {
+                continue;
+            }
+
+            let to = Square::at_index(i);
+            let to_bitmap = to.to_bitmap();
+            let mut new_own_color_bitmap = (own_color_bitmap & !piece.pos.to_bitmap()) | to_bitmap;
+            let mut new_other_color_bitmap = other_color_bitmap & !to_bitmap;
+
+            let en_passant = piece.piece_type == PieceType::Pawn && piece.pos.x != to.x && other_color_bitmap & to_bitmap == 0;
+            if en_passant {
+                new_other_color_bitmap &= !to.moved(0, -piece.get_direction()).to_bitmap();
+            }
+
+            // the capturing piece and everything around it except pawns explodes
+            if en_passant || other_color_bitmap & to_bitmap != 0 {
+                let blast = to_bitmap | (neighbours(to) & !pawns_bitmap);
+                new_own_color_bitmap &= !blast;
+                new_other_color_bitmap &= !blast;
+            }
+
+            let own_king_bitmap = if piece.piece_type == PieceType::King { to_bitmap } else { own_king_bitmap };
+            if new_own_color_bitmap & own_king_bitmap == 0 {
+                legal &= !to_bitmap;
+                continue;
+            }
+            if new_other_color_bitmap & other_king_bitmap == 0 || neighbours(Square::at_index(own_king_bitmap.trailing_zeros() as i8)) & other_king_bitmap != 0 {
+                continue;
+            }
+
+            // kings can not capture so they never attack
+            let in_check = self.live_pieces.values()
+                .filter(|p| p.color != piece.color && p.piece_type != PieceType::King && p.pos.to_bitmap() & new_other_color_bitmap != 0)
+                .any(|p| self.psuedo_legal_moves(p, new_other_color_bitmap, new_own_color_bitmap) & own_king_bitmap != 0);
+            if in_check {
+                legal &= !to_bitmap;
+            }
+        }
+
+        legal
+    }
+
+    // removes the capturing piece on the square and all pieces around it that are not pawns, a pawn that explodes
+    // on the last rank is not promoted
+    pub(crate) fn explode(&mut self, square: Square) {
+        self.capture(&square);
+        for i in 0..64 {
+            let other = Square::at_index(i);
+            if neighbours(square) & other.to_bitmap() != 0 && self.live_pieces.get(&other).is_some_and(|p| p.piece_type != PieceType::Pawn) {
+                self.capture(&other);
+            }
+        }
+        self.promotion = false;
+    }
+
+    // returns the moves that capture something, including en passant
+    fn captures(&self, piece: &Piece, moves: u64) -> u64 {
+        let other_color_bitmap = match piece.color {
+            PieceColor::White => self.black_bitmap,
+            PieceColor::Black => self.white_bitmap,
+        };
+        let mut captures = moves & other_color_bitmap;
+        if piece.piece_type == PieceType::Pawn {
+            // diagonal pawn moves are always captures
+            captures |= moves & (piece.pos.moved(1, piece.get_direction()).to_bitmap() | piece.pos.moved(-1, piece.get_direction()).to_bitmap());
+        }
+        captures
+    }
+
+    // returns true if moving the piece to the square puts the other king in check
+    fn gives_check(&self, piece: &Piece, to: Square) -> bool {
+        let (own_color_bitmap, other_color_bitmap) = match piece.color {
+            PieceColor::White => (self.white_bitmap, self.black_bitmap),
+            PieceColor::Black => (self.black_bitmap, self.white_bitmap),
+        };
+        let new_own_color_bitmap = (own_color_bitmap & !piece.pos.to_bitmap()) | to.to_bitmap();
+        let new_other_color_bitmap = other_color_bitmap & !to.to_bitmap();
+
+        let other_king_bitmap = match self.live_pieces.values().find(|p| p.piece_type == PieceType::King && p.color != piece.color) {
+            Some(king) => king.pos.to_bitmap(),
+            None => return false,
+        };
+
+        let moved = Piece { pos: to, ..piece.clone() };
+        self.live_pieces.values()
+            .filter(|p| p.color == piece.color && p.pos != piece.pos)
+            .chain(std::iter::once(&moved))
+            .any(|p| self.psuedo_legal_moves(p, new_own_color_bitmap, new_other_color_bitmap) & other_king_bitmap != 0)
+    }
+
+    // returns true if any piece of the color can capture something
+    fn has_capture(&self, color: PieceColor) -> bool {
+        let (own_color_bitmap, other_color_bitmap) = match color {
+            PieceColor::White => (self.white_bitmap, self.black_bitmap),
+            PieceColor::Black => (self.black_bitmap, self.white_bitmap),
+        };
+        self.live_pieces.values()
+            .filter(|p| p.color == color)
+            .any(|p| self.captures(p, self.psuedo_legal_moves(p, own_color_bitmap, other_color_bitmap)) != 0)
+    }
+
+    fn kings_touching(&self) -> bool {
+        let mut kings = self.live_pieces.values().filter(|p| p.piece_type == PieceType::King);
+        match (kings.next(), kings.next()) {
+            (Some(a), Some(b)) => neighbours(a.pos) & b.pos.to_bitmap() != 0,
+            _ => false,
+        }
+    }
+}
+
+// returns a bitmap of the up to eight squares around a square
+fn neighbours(square: Square) -> u64 {
+    let mut bitmap = 0;
+    for (dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)] {
+        bitmap |= square.moved(dx, dy).to_bitmap();
+    }
+    bitmap
+}
+
+fn win(color: PieceColor) -> ChessResult {
+    match color {
+        PieceColor::White => ChessResult::WhiteWon,
+        PieceColor::Black => ChessResult::BlackWon,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Move, Square};
+
+    #[test]
+    fn test_king_of_the_hill() {
+        let mut game = Game::new_variant(Variant::KingOfTheHill);
+        for uci in ["e2e4", "a7a6", "e1e2", "a6a5", "e2d3", "a5a4"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        assert_eq!(game.result, ChessResult::Ongoing);
+        assert!(game.make_move(&Move::from_uci("d3d4").unwrap()));
+        assert_eq!(game.result, ChessResult::WhiteWon);
+
+        // a lone king is not a draw
+        let game = Game::from_fen_variant("8/8/8/8/8/8/k7/7K w - - 0 1", Variant::KingOfTheHill);
+        assert_eq!(game.result, ChessResult::Ongoing);
+        let mut game = Game::from_fen_variant("8/8/8/8/8/2K5/k7/8 w - - 0 1", Variant::KingOfTheHill);
+        assert!(game.do_move(&Square::C3, &Square::D4));
+        assert_eq!(game.result, ChessResult::WhiteWon);
+        assert_eq!(Variant::from_name("King of the Hill"), Some(Variant::KingOfTheHill));
+    }
+
+    #[test]
+    fn test_three_check() {
+        let mut game = Game::new_variant(Variant::ThreeCheck);
+        for uci in ["e2e4", "e7e5", "f1c4", "d7d6", "c4f7", "e8f7", "d1h5", "g7g6"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        assert!(game.white_checks == 2 && game.black_checks == 0);
+        assert_eq!(game.to_fen(), "rnbq1bnr/ppp2k1p/3p2p1/4p2Q/4P3/8/PPPP1PPP/RNB1K1NR w KQ - 0 5 +2+0");
+
+        // the counters are read back from FEN, both the checks given and the lichess checks left format
+        assert_eq!(Game::from_fen_variant(&game.to_fen(), Variant::ThreeCheck).hash(), game.hash());
+        let other = Game::from_fen_variant("rnbq1bnr/ppp2k1p/3p2p1/4p2Q/4P3/8/PPPP1PPP/RNB1K1NR w KQ - 1+3 0 5", Variant::ThreeCheck);
+        assert!(other.white_checks == 2 && other.to_fen() == game.to_fen());
+
+        // a third check wins even if it is not mate
+        let mut game = Game::from_fen_variant("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0", Variant::ThreeCheck);
+        assert_eq!(game.result, ChessResult::Ongoing);
+        assert!(game.make_move(&Move::from_uci("a1a8").unwrap()));
+        assert_eq!(game.result, ChessResult::WhiteWon);
+    }
+
+    #[test]
+    fn test_crazyhouse() {
+        let mut game = Game::new_variant(Variant::Crazyhouse);
+        assert_eq!(game.to_fen(), Variant::Crazyhouse.starting_fen());
+        for uci in ["e2e4", "d7d5", "e4d5", "d8d5"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        assert!(game.white_pocket == vec![PieceType::Pawn] && game.black_pocket == vec![PieceType::Pawn]);
+        assert_eq!(game.to_fen(), "rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR[Pp] w KQkq - 0 3");
+
+        // pawns are never dropped on the first or last rank
+        assert_eq!(game.get_drops_bitmap(PieceType::Pawn) & 0xFF000000_000000FF, 0);
+        assert_eq!(game.get_drops_bitmap(PieceType::Knight), 0);
+        let drop = game.move_from_san("@e4").unwrap();
+        assert_eq!(game.move_to_san(&drop).unwrap(), "P@e4");
+        assert!(game.make_move(&drop));
+        assert!(game.white_pocket.is_empty() && game.live_pieces.get(&Square::E4).unwrap().has_moved);
+
+        // promoted pieces go back to the pocket as pawns
+        let mut game = Game::from_fen_variant("r3k3/8/8/8/8/8/8/Q~3K3[] b - - 0 1", Variant::Crazyhouse);
+        assert_eq!(game.to_fen(), "r3k3/8/8/8/8/8/8/Q~3K3[] b - - 0 1");
+        assert!(game.make_move(&Move::from_uci("a8a1").unwrap()));
+        assert!(game.black_pocket == vec![PieceType::Pawn] && game.white_captured_pieces == vec![PieceType::Queen]);
+
+        // a drop can block a check and save from mate, the pocket can also be written as a ninth rank
+        let game = Game::from_fen_variant("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1", Variant::Crazyhouse);
+        assert_eq!(game.get_drops_bitmap(PieceType::Knight), 0b1110);
+        let game = Game::from_fen_variant("k7/8/8/8/8/8/5PPP/r5K1/N w - - 0 1", Variant::Crazyhouse);
+        assert_eq!(game.result, ChessResult::Ongoing);
+        let game = Game::from_fen_variant("k7/8/8/8/8/8/5PPP/r5K1[] w - - 0 1", Variant::Crazyhouse);
+        assert_eq!(game.result, ChessResult::BlackWon);
+        assert!(game.hash() != Game::from_fen_variant("k7/8/8/8/8/8/5PPP/r5K1[n] w - - 0 1", Variant::Crazyhouse).hash());
+    }
+
+    #[test]
+    fn test_atomic() {
+        let mut game = Game::new_variant(Variant::Atomic);
+        for uci in ["e2e4", "d7d5", "e4d5"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        assert_eq!(game.to_fen(), "rnbqkbnr/ppp1pppp/8/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
+
+        // the knight explodes the king next to f7
+        for uci in ["a7a6", "g1f3", "a6a5", "f3g5", "a5a4"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        assert!(game.make_move(&Move::from_uci("g5f7").unwrap()));
+        assert_eq!(game.result, ChessResult::WhiteWon);
+        assert!(game.black_captured_pieces.contains(&PieceType::King));
+
+        // kings can not capture and a capture may not explode the own king
+        let game = Game::from_fen_variant("4k3/8/8/8/8/8/3q4/3QK3 w - - 0 1", Variant::Atomic);
+        assert!(game.check);
+        assert_eq!(game.get_moves_bitmap(&Square::E1) & Square::D2.to_bitmap(), 0);
+        assert_eq!(game.get_moves_bitmap(&Square::D1) & Square::D2.to_bitmap(), 0);
+
+        // kings next to each other are never in check
+        let game = Game::from_fen_variant("8/8/8/8/8/8/3k4/r3K3 w - - 0 1", Variant::Atomic);
+        assert!(!game.check);
+        assert_eq!(game.get_moves_bitmap(&Square::E1) & Square::F1.to_bitmap(), 0);
+        assert!(game.get_moves_bitmap(&Square::E1) & Square::E2.to_bitmap() != 0);
+    }
+
+    #[test]
+    fn test_antichess() {
+        // captures are compulsory
+        let mut game = Game::new_variant(Variant::Antichess);
+        for uci in ["e2e3", "b7b5"] {
+            assert!(game.make_move(&Move::from_uci(uci).unwrap()));
+        }
+        let moves: Vec<String> = game.get_all_moves().iter().map(|m| m.to_uci()).collect();
+        assert_eq!(moves, vec!["f1b5"]);
+
+        // the king can be captured, there is no check and pawns can become kings
+        let game = Game::from_fen_variant("8/1P6/8/8/8/8/8/r3K3 w - - 0 1", Variant::Antichess);
+        assert!(!game.check && game.result == ChessResult::Ongoing);
+        assert!(game.get_moves_bitmap(&Square::E1) & Square::D1.to_bitmap() != 0);
+        assert!(game.get_all_moves().iter().any(|m| m.promotion == Some(PieceType::King)));
+        let game = Game::from_fen_variant("8/8/8/8/8/8/8/r3K2R w K - 0 1", Variant::Antichess);
+        assert_eq!(game.get_moves_bitmap(&Square::E1) & Square::G1.to_bitmap(), 0);
+
+        // losing every piece wins
+        let mut game = Game::from_fen_variant("8/8/8/8/8/8/8/rR6 w - - 0 1", Variant::Antichess);
+        assert!(game.make_move(&Move::from_uci("b1a1").unwrap()));
+        assert_eq!(game.result, ChessResult::BlackWon);
+
+        // so does being stalemated
+        let game = Game::from_fen_variant("8/8/8/8/8/p7/P7/8 w - - 0 1", Variant::Antichess);
+        assert_eq!(game.result, ChessResult::WhiteWon);
+    }
+
+    #[test]
+    fn test_horde() {
+        let game = Game::new_variant(Variant::Horde);
+        assert_eq!(game.to_fen(), Variant::Horde.starting_fen());
+        // pawns on the first rank can move two steps
+        assert_eq!(game.get_moves_bitmap(&Square::A1), 0);
+        let game = Game::from_fen_variant("4k3/8/8/8/8/8/8/P7 w - - 0 1", Variant::Horde);
+        assert!(game.get_moves_bitmap(&Square::A1) == Square::A2.to_bitmap() | Square::A3.to_bitmap());
+
+        // black wins by capturing every white piece
+        let mut game = Game::from_fen_variant("4k3/8/8/8/8/8/1r6/P7 b - - 0 1", Variant::Horde);
+        assert!(game.make_move(&Move::from_uci("b2b1").unwrap()));
+        assert_eq!(game.result, ChessResult::Ongoing);
+        assert!(game.make_move(&Move::from_uci("a1a2").unwrap()));
+        assert!(game.make_move(&Move::from_uci("b1a1").unwrap()));
+        assert!(game.make_move(&Move::from_uci("a2a3").unwrap()));
+        assert!(game.make_move(&Move::from_uci("a1a3").unwrap()));
+        assert_eq!(game.result, ChessResult::BlackWon);
+    }
+
+    #[test]
+    fn test_racing_kings() {
+        // checks are not allowed
+        let game = Game::new_variant(Variant::RacingKings);
+        assert!(game.get_moves_bitmap(&Square::E2) & Square::D4.to_bitmap() != 0);
+        assert_eq!(game.get_moves_bitmap(&Square::E2) & Square::C3.to_bitmap(), 0);
+        assert_eq!(game.get_all_moves().len(), 21);
+
+        // black can still draw after white reaches the eighth rank
+        let mut game = Game::from_fen_variant("8/5K1k/8/8/8/8/8/8 w - - 0 1", Variant::RacingKings);
+        assert!(game.make_move(&Move::from_uci("f7e8").unwrap()));
+        assert_eq!(game.result, ChessResult::Ongoing);
+        assert!(game.make_move(&Move::from_uci("h7h8").unwrap()));
+        assert_eq!(game.result, ChessResult::Draw);
+
+        let mut game = Game::from_fen_variant("8/5K2/8/7k/8/8/8/8 w - - 0 1", Variant::RacingKings);
+        assert!(game.make_move(&Move::from_uci("f7f8").unwrap()));
+        assert_eq!(game.result, ChessResult::WhiteWon);
+    }
+}
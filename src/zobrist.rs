@@ -0,0 +1,35 @@
+// Random keys used by Game::hash() (Zobrist hashing).
+// Every piece on every square, every castling right, every en passant file and the side to move has its own key,
+// the hash of a position is all the keys that apply XORed together.
+//
+// Layout of KEYS:
+//  0..768   pieces, index (color * 6 + piece type) * 64 + square index
+//  768..772 castling rights, white short, white long, black short, black long
+//  772..780 en passant file
+//  780      black to move
+//  781..789 checks given in Three-check, white 0-3 then black 0-3
+//  789..949 Crazyhouse pockets, index (color * 5 + piece) * 16 + count, pieces in the order Q R B N P
+
+pub(crate) const CASTLING_OFFSET: usize = 768;
+pub(crate) const EN_PASSANT_OFFSET: usize = 772;
+pub(crate) const BLACK_TO_MOVE: usize = 780;
+pub(crate) const CHECKS_OFFSET: usize = 781;
+pub(crate) const POCKET_OFFSET: usize = 789;
+
+pub(crate) static KEYS: [u64; 949] = generate_keys();
+
+// fills the table using splitmix64 with a fixed seed so hashes are the same on every run
+const fn generate_keys() -> [u64; 949] {
+    let mut keys = [0; 949];
+    let mut state: u64 = 0x0123_4567_89AB_CDEF;
+    let mut i = 0;
+    while i < keys.len() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
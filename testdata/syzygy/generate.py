@@ -0,0 +1,356 @@
+#!/usr/bin/env python3
+# Writes the KQvK and KRvK Syzygy tables used by the tablebase tests.
+#
+# The values come from a retrograde analysis of the endgame and are encoded in the Syzygy format: the pieces are
+# indexed like the generator does for three unique pieces, the values are compressed by pairing symbols and a
+# canonical Huffman code, and the DTZ tables map their values through a map sorted by frequency. The tables are small
+# enough to check in, run this script in this directory to write them again.
+
+import heapq
+from collections import Counter
+
+WDL_MAGIC = bytes([0x71, 0xE8, 0x23, 0x5D])
+DTZ_MAGIC = bytes([0xD7, 0x66, 0x0C, 0xA5])
+
+FLAG_MAPPED = 2
+FLAG_SINGLE_VALUE = 128
+
+KING_STEPS = [(dx, dy) for dx in (-1, 0, 1) for dy in (-1, 0, 1) if dx or dy]
+ROOK_DIRECTIONS = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+QUEEN_DIRECTIONS = ROOK_DIRECTIONS + [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+
+
+def file_of(s):
+    return s & 7
+
+
+def rank_of(s):
+    return s >> 3
+
+
+def adjacent(a, b):
+    return max(abs(file_of(a) - file_of(b)), abs(rank_of(a) - rank_of(b))) <= 1
+
+
+def king_moves(s):
+    for dx, dy in KING_STEPS:
+        x, y = file_of(s) + dx, rank_of(s) + dy
+        if 0 <= x < 8 and 0 <= y < 8:
+            yield y * 8 + x
+
+
+def slides(s, directions, blockers):
+    # the squares a piece reaches, including the first blocker
+    for dx, dy in directions:
+        x, y = file_of(s) + dx, rank_of(s) + dy
+        while 0 <= x < 8 and 0 <= y < 8:
+            yield y * 8 + x
+            if y * 8 + x in blockers:
+                break
+            x, y = x + dx, y + dy
+
+
+def solve(directions):
+    # returns the plies to mate of the white to move positions (all of them are won) and of the black to move
+    # positions that are lost, the black to move positions that are left out are draws
+    legal_btm = []
+    legal_wtm = []
+    for wk in range(64):
+        for x in range(64):
+            for bk in range(64):
+                if len({wk, x, bk}) < 3 or adjacent(wk, bk):
+                    continue
+                legal_btm.append((wk, x, bk))
+                if bk not in slides(x, directions, {wk, bk}):
+                    legal_wtm.append((wk, x, bk))
+
+    def white_children(p):
+        wk, x, bk = p
+        for to in king_moves(wk):
+            if to != x and not adjacent(to, bk):
+                yield (to, x, bk)
+        for to in slides(x, directions, {wk, bk}):
+            if to not in (wk, bk):
+                yield (wk, to, bk)
+
+    # None for a move that draws by taking the piece
+    def black_children(p):
+        wk, x, bk = p
+        for to in king_moves(bk):
+            if to == wk or adjacent(to, wk):
+                continue
+            if to == x:
+                yield None
+            elif to not in slides(x, directions, {wk}):
+                yield (wk, x, to)
+
+    btm_children = {p: list(black_children(p)) for p in legal_btm}
+    wtm_children = {p: list(white_children(p)) for p in legal_wtm}
+
+    btm = {p: 0 for p, children in btm_children.items() if not children and p[2] in slides(p[1], directions, {p[0]})}
+    wtm = {}
+    ply = 1
+    last_change = 0
+    while ply - last_change <= 2:
+        if ply % 2 == 1:
+            for p, children in wtm_children.items():
+                if p not in wtm and any(btm.get(c) == ply - 1 for c in children):
+                    wtm[p] = ply
+                    last_change = ply
+        else:
+            for p, children in btm_children.items():
+                if p not in btm and children and None not in children and all(c in wtm for c in children):
+                    btm[p] = ply
+                    last_change = ply
+        ply += 1
+
+    assert len(wtm) == len(legal_wtm)
+    return wtm, btm, legal_btm
+
+
+# the index of three unique pieces, like the generator and Tablebase::probe()
+def off_a1h8(s):
+    return rank_of(s) - file_of(s)
+
+
+MAP_B1H1H7 = {}
+MAP_A1D1D4 = {}
+
+
+def init_maps():
+    code = 0
+    for s in range(64):
+        if off_a1h8(s) < 0:
+            MAP_B1H1H7[s] = code
+            code += 1
+    code = 0
+    diagonal = []
+    for s in range(28):
+        if off_a1h8(s) < 0 and file_of(s) <= 3:
+            MAP_A1D1D4[s] = code
+            code += 1
+        elif off_a1h8(s) == 0 and file_of(s) <= 3:
+            diagonal.append(s)
+    for s in diagonal:
+        MAP_A1D1D4[s] = code
+        code += 1
+
+
+def index(squares):
+    sq = list(squares)
+    if file_of(sq[0]) > 3:
+        sq = [s ^ 7 for s in sq]
+    if rank_of(sq[0]) > 3:
+        sq = [s ^ 56 for s in sq]
+    for i in range(3):
+        if off_a1h8(sq[i]) == 0:
+            continue
+        if off_a1h8(sq[i]) > 0:
+            for j in range(i, 3):
+                sq[j] = ((sq[j] >> 3) | (sq[j] << 3)) & 63
+        break
+
+    adjust1 = int(sq[1] > sq[0])
+    adjust2 = int(sq[2] > sq[0]) + int(sq[2] > sq[1])
+    if off_a1h8(sq[0]) != 0:
+        return (MAP_A1D1D4[sq[0]] * 63 + (sq[1] - adjust1)) * 62 + sq[2] - adjust2
+    if off_a1h8(sq[1]) != 0:
+        return (6 * 63 + rank_of(sq[0]) * 28 + MAP_B1H1H7[sq[1]]) * 62 + sq[2] - adjust2
+    if off_a1h8(sq[2]) != 0:
+        return 6 * 63 * 62 + 4 * 28 * 62 + rank_of(sq[0]) * 7 * 28 + (rank_of(sq[1]) - adjust1) * 28 + MAP_B1H1H7[sq[2]]
+    return 6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank_of(sq[0]) * 7 * 6 + (rank_of(sq[1]) - adjust1) * 6 + (rank_of(sq[2]) - adjust2)
+
+
+TB_SIZE = 31332
+
+
+def table_values(values, fill):
+    # values of positions by index, every position that shares an index must have the same value
+    table = [None] * TB_SIZE
+    for (wk, x, bk), value in values.items():
+        i = index((wk, x, bk))
+        assert table[i] is None or table[i] == value, "two positions with different values share an index"
+        table[i] = value
+    return [fill if value is None else value for value in table]
+
+
+def compress(values, block_size=32, span=256):
+    # returns the sub-table header, the sparse index, the block lengths and the blocks
+    leaves = sorted(set(values))
+    if len(leaves) == 1:
+        return None, b'', b'', b''
+
+    # pairing: the most common pair of symbols becomes a new symbol, a symbol never expands to more than 256 values
+    pairs = [(v, 0xFFF) for v in leaves]
+    lengths = [1] * len(leaves)
+    stream = [leaves.index(v) for v in values]
+    while len(pairs) < 1000:
+        counts = Counter(zip(stream, stream[1:]))
+        (a, b), count = counts.most_common(1)[0]
+        if count < 16 or lengths[a] + lengths[b] > 256:
+            break
+        new = len(pairs)
+        pairs.append((a, b))
+        lengths.append(lengths[a] + lengths[b])
+        replaced = []
+        i = 0
+        while i < len(stream):
+            if i + 1 < len(stream) and stream[i] == a and stream[i + 1] == b:
+                replaced.append(new)
+                i += 2
+            else:
+                replaced.append(stream[i])
+                i += 1
+        stream = replaced
+
+    # huffman code lengths of the symbols in the stream
+    frequency = Counter(stream)
+    heap = [(count, sym, [sym]) for sym, count in frequency.items()]
+    heapq.heapify(heap)
+    code_len = {sym: 0 for sym in frequency}
+    while len(heap) > 1:
+        c1, s1, l1 = heapq.heappop(heap)
+        c2, s2, l2 = heapq.heappop(heap)
+        for sym in l1 + l2:
+            code_len[sym] += 1
+        heapq.heappush(heap, (c1 + c2, min(s1, s2), l1 + l2))
+    min_len = min(code_len.values())
+    max_len = max(code_len.values())
+    assert max_len <= 32
+
+    # canonical numbering, the longest codes get the lowest symbols, symbols without a code come last
+    coded = sorted(frequency, key=lambda sym: (-code_len[sym], sym))
+    uncoded = [sym for sym in range(len(pairs)) if sym not in frequency]
+    order = coded + uncoded
+    renumber = {old: new for new, old in enumerate(order)}
+    btree = []
+    for old in order:
+        a, b = pairs[old]
+        btree.append((a, 0xFFF) if b == 0xFFF else (renumber[a], renumber[b]))
+    stream = [renumber[sym] for sym in stream]
+    lens = {renumber[sym]: length for sym, length in code_len.items()}
+    expansion = [lengths[old] for old in order]
+
+    count = [0] * (max_len - min_len + 1)
+    for length in lens.values():
+        count[length - min_len] += 1
+    lowest = [0] * len(count)
+    base = [0] * len(count)
+    for i in range(len(count) - 2, -1, -1):
+        lowest[i] = lowest[i + 1] + count[i + 1]
+        assert (base[i + 1] + count[i + 1]) % 2 == 0
+        base[i] = (base[i + 1] + count[i + 1]) // 2
+    assert base[0] + count[0] == 1 << min_len
+
+    def code(sym):
+        i = lens[sym] - min_len
+        return base[i] + sym - lowest[i], lens[sym]
+
+    # the blocks hold whole symbols, written as big-endian bits
+    blocks = []
+    block_values = []
+    bits = ''
+    values_in_block = 0
+    for sym in stream:
+        value, length = code(sym)
+        if len(bits) + length > block_size * 8:
+            blocks.append(bits)
+            block_values.append(values_in_block)
+            bits, values_in_block = '', 0
+        bits += format(value, '0{}b'.format(length))
+        values_in_block += expansion[sym]
+    blocks.append(bits)
+    block_values.append(values_in_block)
+    assert max(block_values) <= 65536
+
+    data = b''.join(int(b.ljust(block_size * 8, '0'), 2).to_bytes(block_size, 'big') for b in blocks)
+    block_length = b''.join((n - 1).to_bytes(2, 'little') for n in block_values)
+
+    starts = [0]
+    for n in block_values:
+        starts.append(starts[-1] + n)
+    sparse = b''
+    for k in range((TB_SIZE + span - 1) // span):
+        position = k * span + span // 2
+        block = len(block_values) - 1
+        for b in range(len(block_values)):
+            if position < starts[b + 1]:
+                block = b
+                break
+        sparse += block.to_bytes(4, 'little') + (position - starts[block]).to_bytes(2, 'little')
+
+    header = bytes([block_size.bit_length() - 1, span.bit_length() - 1, 0])
+    header += len(blocks).to_bytes(4, 'little') + bytes([max_len, min_len])
+    header += b''.join(x.to_bytes(2, 'little') for x in lowest)
+    header += len(btree).to_bytes(2, 'little')
+    for a, b in btree:
+        header += bytes([a & 0xFF, (a >> 8) | ((b & 0xF) << 4), b >> 4])
+    if len(btree) % 2:
+        header += b'\0'
+    return header, sparse, block_length, data
+
+
+def write_table(path, magic, split, pieces, subtables, dtz_map=None):
+    # pieces are the codes of the table in index order, subtables are (flags, values) for white and black to move
+    out = bytearray(magic)
+    out.append(1 if split else 0)
+    out.append(0)
+    for piece in pieces:
+        out.append(piece | (piece << 4))
+    out += b'\0' * (len(out) & 1)
+
+    parts = []
+    for flags, values in subtables:
+        header, sparse, block_length, data = compress(values)
+        if header is None:
+            header = bytes([FLAG_SINGLE_VALUE, values[0]])
+        else:
+            header = bytes([flags]) + header
+        parts.append((header, sparse, block_length, data))
+        out += header
+
+    if dtz_map is not None:
+        for values in dtz_map:
+            out.append(len(values))
+            out += bytes(values)
+        out += b'\0' * (len(out) & 1)
+
+    for part in parts:
+        out += part[1]
+    for part in parts:
+        out += part[2]
+    for part in parts:
+        out += b'\0' * (-len(out) % 64)
+        out += part[3]
+
+    with open(path, 'wb') as f:
+        f.write(out)
+
+
+def generate(name, piece, directions, longest):
+    wtm, btm, legal_btm = solve(directions)
+    assert max(wtm.values()) == longest and max(btm.values()) == longest + 1
+
+    # white king, the piece and the black king
+    pieces = [6, piece, 14]
+
+    # wdl, 4 is a win, 2 a draw and 0 a loss for the side to move
+    wdl_white = table_values({p: 4 for p in wtm}, 4)
+    wdl_black = {p: 0 if p in btm else 2 for p in legal_btm}
+    fill = Counter(wdl_black.values()).most_common(1)[0][0]
+    wdl_black = table_values(wdl_black, fill)
+    write_table(name + '.rtbw', WDL_MAGIC, True, pieces, [(0, wdl_white), (0, wdl_black)])
+
+    # dtz of white to move, the table stores (dtz - 1) / 2 through a map with the most common value first
+    stored = {p: (v - 1) // 2 for p, v in wtm.items()}
+    by_frequency = [value for value, _ in Counter(stored.values()).most_common()]
+    symbol = {value: i for i, value in enumerate(by_frequency)}
+    dtz_white = table_values({p: symbol[v] for p, v in stored.items()}, 0)
+    write_table(name + '.rtbz', DTZ_MAGIC, False, pieces, [(FLAG_MAPPED, dtz_white)], [by_frequency, [], [], []])
+
+
+if __name__ == '__main__':
+    init_maps()
+    # the longest wins are mate in 10 and mate in 16
+    generate('KQvK', 5, QUEEN_DIRECTIONS, 19)
+    generate('KRvK', 4, ROOK_DIRECTIONS, 31)